@@ -0,0 +1,228 @@
+use crate::app::*;
+use crate::game::*;
+use crate::keymap::Screen;
+use crate::menu::{self, ItemKind};
+use std::fs::OpenOptions;
+use std::io::{Result, Write};
+use std::path::Path;
+
+/// A screen told as plain lines of text, for screen readers and the text mode.
+pub struct TextScreen {
+    pub title: String,
+    pub notice: Option<String>,
+    /// Lines to read before the items, like how a match ended.
+    pub info: Vec<String>,
+    pub items: Vec<String>,
+    /// Index of the focused item.
+    pub selected: Option<usize>,
+}
+
+/// Tells the screen the app is showing as text, `None` while a match is being played.
+pub fn describe(app: &App) -> Option<TextScreen> {
+    let buttons = |names: &[&str], skip: usize, selected: i8| TextScreen {
+        title: String::new(),
+        notice: app.notice.clone(),
+        info: vec![],
+        items: names[skip..].iter().map(|name| name.to_string()).collect(),
+        selected: (selected as usize).checked_sub(skip),
+    };
+
+    let screen = match Screen::current(app) {
+        Screen::Playing => return None,
+        Screen::Confirm => {
+            let confirm = app.confirm?;
+            TextScreen {
+                title: confirm.question().to_string(),
+                info: vec!["The match in progress will be lost.".to_string()],
+                ..buttons(&CONFIRM_MENU_BUTTONS, 0, app.confirm_selected_button)
+            }
+        }
+        Screen::Help => TextScreen {
+            title: "Help".to_string(),
+            notice: None,
+            info: vec!["Press Esc to close this window.".to_string()],
+            items: vec![],
+            selected: None,
+        },
+        Screen::Paused => TextScreen {
+            title: "Paused".to_string(),
+            ..buttons(&PAUSE_MENU_BUTTONS, 0, app.game.pause_menu_selected_button)
+        },
+        Screen::GameOver => TextScreen {
+            title: "Game Over".to_string(),
+            info: result_lines(&app.game),
+            ..buttons(
+                &GAME_OVER_MENU_BUTTONS,
+                0,
+                app.game.game_over_menu_selected_button,
+            )
+        },
+        Screen::MainMenu => TextScreen {
+            title: "Pong-TUI".to_string(),
+            ..buttons(
+                &MAIN_MENU_BUTTONS,
+                app.saved.is_none() as usize,
+                app.main_menu_selected_button,
+            )
+        },
+        Screen::Settings | Screen::CustomDifficulty | Screen::Setup | Screen::TextInput => {
+            let menu = menu::shown(app)?;
+            let title = if app.settings_menu_shown {
+                "Settings"
+            } else if app.custom_menu_shown {
+                "Custom Difficulty"
+            } else {
+                "New Game"
+            };
+            let shown = menu.shown(app);
+            let selected = menu.selected(app);
+            let editing = &(menu.state)(app).editing;
+            let items = shown
+                .iter()
+                .map(|&i| {
+                    let item = &menu.items[i];
+                    match (&item.kind, editing) {
+                        (ItemKind::Heading, _) => item.label.to_string(),
+                        (ItemKind::Text { .. }, Some(input)) if i == selected => {
+                            format!("{} {} (typing)", item.label, input.text())
+                        }
+                        _ => match item.value(app) {
+                            Some(value) => format!("{} {}", item.label, value.trim()),
+                            None => item.label.to_string(),
+                        },
+                    }
+                })
+                .collect();
+            TextScreen {
+                title: title.to_string(),
+                notice: app.notice.clone(),
+                info: vec![],
+                items,
+                selected: shown.iter().position(|&i| i == selected),
+            }
+        }
+    };
+    Some(screen)
+}
+
+/// How the match ended, a line each.
+fn result_lines(game: &Game) -> Vec<String> {
+    if game.mode.is_solo() {
+        let elapsed = game.elapsed.as_secs();
+        vec![
+            format!("Score {}", game.score(Side::Left)),
+            format!("Survived {}:{:02}", elapsed / 60, elapsed % 60),
+            format!("Best streak {}", game.best_streak),
+        ]
+    } else {
+        let mut lines = vec![score_text(game)];
+        if let Some(side) = game.winner() {
+            lines.insert(0, format!("{} Wins!", game.player_name(side)));
+        }
+        lines
+    }
+}
+
+/// The score of every player, in one line.
+pub fn score_text(game: &Game) -> String {
+    match game.mode {
+        GameMode::Versus => format!(
+            "{} {}, {} {}",
+            game.player_name(Side::Left),
+            game.score(Side::Left),
+            game.player_name(Side::Right),
+            game.score(Side::Right)
+        ),
+        GameMode::FourPlayer => Side::ALL
+            .map(|side| match game.lives[side.index()] {
+                0 => format!("{} out", game.player_name(side)),
+                lives => format!("{} {lives} lives", game.player_name(side)),
+            })
+            .join(", "),
+        GameMode::Practice | GameMode::Breakout => format!(
+            "Score {}, {} lives",
+            game.score(Side::Left),
+            game.lives[Side::Left.index()]
+        ),
+    }
+}
+
+/// Writes short lines about what happens to a file or FIFO, for a screen reader to pick up.
+#[derive(Default)]
+pub struct Announcer {
+    out: Option<Box<dyn Write>>,
+    /// Title, notice and focused line last said, each said again only once it changes.
+    said: (String, Option<String>, String),
+}
+
+impl Announcer {
+    /// Appends to `path`. Opening a FIFO waits until something reads from it.
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new().append(true).create(true).open(path)?;
+        Ok(Self {
+            out: Some(Box::new(file)),
+            said: Default::default(),
+        })
+    }
+
+    /// Whether there is still somewhere to say things, so screens aren't described for nothing.
+    pub fn is_open(&self) -> bool {
+        self.out.is_some()
+    }
+
+    fn say(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        if let Some(out) = &mut self.out {
+            // Like sound, a reader that went away is not worth retrying every frame.
+            if writeln!(out, "{text}").and_then(|()| out.flush()).is_err() {
+                self.out = None;
+            }
+        }
+    }
+
+    /// Says what changed on `screen` since the last call, the title only when it is new.
+    pub fn update(&mut self, screen: Option<TextScreen>, game: &Game) {
+        if self.out.is_none() {
+            return;
+        }
+        let Some(screen) = screen else {
+            if !self.said.0.is_empty() {
+                self.said = Default::default();
+                self.say(&format!("Playing. {}", score_text(game)));
+            }
+            return;
+        };
+
+        let focused = screen
+            .selected
+            .and_then(|i| screen.items.get(i))
+            .cloned()
+            .unwrap_or_default();
+        if screen.title != self.said.0 {
+            self.say(&screen.title);
+            for line in &screen.info {
+                self.say(line);
+            }
+            self.say(&focused);
+        } else if focused != self.said.2 {
+            self.say(&focused);
+        }
+        if screen.notice.is_some() && screen.notice != self.said.1 {
+            self.say(screen.notice.as_deref().unwrap_or_default());
+        }
+        self.said = (screen.title, screen.notice, focused);
+    }
+
+    /// Says the score after every goal.
+    pub fn handle(&mut self, event: GameEvent, game: &Game) {
+        if let GameEvent::Goal { scorer, conceder } = event {
+            let goal = match scorer {
+                Some(side) => format!("{} scores", game.player_name(side)),
+                None => format!("{} misses", game.player_name(conceder)),
+            };
+            self.say(&format!("{goal}. {}", score_text(game)));
+        }
+    }
+}
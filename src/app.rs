@@ -1,21 +1,100 @@
-use crate::game::{Game, Difficulty};
+use crate::announce::Announcer;
+use crate::arena::Arena;
+use crate::audio::Audio;
+use crate::controller::{Player, Predictor};
+use crate::difficulty::*;
+use crate::effects::Effects;
+use crate::game::{Game, GameMode, Side};
+use crate::menu::{self, MenuState};
+use crate::rules::*;
+use crate::save::{self, SavedMatch};
+use crate::settings::Settings;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use ratatui::layout::Rect;
+use std::time::Duration;
 
-pub const MAIN_MENU_BUTTON_COUNT:usize = 3;
-pub const DIFFICULTY_MENU_BUTTON_COUNT:usize = 4;
-pub const DIFFICULTY_MENU_CHOICE_COUNT:usize = 2;
+pub const MAIN_MENU_BUTTON_COUNT: usize = 6;
+pub const CONFIRM_MENU_BUTTON_COUNT: usize = 2;
+pub const MAIN_MENU_BUTTONS: [&str; MAIN_MENU_BUTTON_COUNT] =
+    ["Continue", "New Game", "Watch", "Settings", "Help", "Quit"];
+pub const CONFIRM_MENU_BUTTONS: [&str; CONFIRM_MENU_BUTTON_COUNT] = ["Yes", "No"];
+
+/// How long the main menu sits untouched before a computer match starts playing behind it.
+pub const ATTRACT_DELAY: Duration = Duration::from_secs(15);
+
+/// Something that throws away a match in progress, so it is only done once the player agrees.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Confirm {
+    Quit,
+    Restart,
+    MainMenu,
+}
+
+impl Confirm {
+    pub fn question(&self) -> &'static str {
+        match *self {
+            Self::Quit => "Quit the game?",
+            Self::Restart => "Set up a new match?",
+            Self::MainMenu => "Go back to the main menu?",
+        }
+    }
+}
 
 pub struct App {
     pub should_quit: bool,
+    /// Question shown over everything else, waiting for a yes or no.
+    pub confirm: Option<Confirm>,
+    pub confirm_selected_button: i8,
     pub main_menu_shown: bool,
     pub main_menu_selected_button: i8,
+    /// Match put aside with "Save & Quit", offered as "Continue" on the main menu.
+    pub saved: Option<SavedMatch>,
+    /// Message shown on the main menu or the pause menu until the next key press.
+    pub notice: Option<String>,
+    /// Time since the last key press on the main menu.
+    pub idle: Duration,
+    /// A computer match is playing behind the main menu.
+    pub attract: bool,
     pub help_popup_shown: bool,
+    /// Lines the help window is scrolled down by.
+    pub help_scroll: u16,
+    pub settings_menu_shown: bool,
+    pub settings_menu: MenuState,
+    pub settings: Settings,
     pub difficulty_menu_shown: bool,
-    pub difficulty_menu_selected_button: i8,
+    pub setup_menu: MenuState,
+    /// The custom difficulty editor, opened from the setup menu.
+    pub custom_menu_shown: bool,
+    pub custom_menu: MenuState,
+    /// What `Difficulty::Custom` sets, kept in the config directory.
+    pub custom_difficulty: DifficultyParams,
+    pub game_mode: i8,
     pub difficulty: i8,
+    pub scoring_rule: i8,
     pub win_score: u16,
+    pub time_limit_minutes: u16,
+    pub server_rule: i8,
+    pub serve_control: i8,
+    /// Built in arenas followed by the ones loaded from the config directory.
+    pub arenas: Vec<Arena>,
+    pub arena: i8,
+    pub left_player: i8,
+    pub right_player: i8,
+    /// Handicaps of the left and right players.
+    pub handicaps: [Handicap; 2],
+    /// Names of the left and right players, empty for none.
+    pub names: [String; 2],
+    /// Seeds the match so it plays out the same every time, `None` for a random one.
+    pub seed: Option<u64>,
+    /// Program started for `Player::Bot`, given with `--bot <command>` or typed into the setup
+    /// menu.
+    pub bot_command: Option<String>,
     pub game_started: bool,
     pub game: Game,
+    pub effects: Effects,
+    pub audio: Audio,
+    pub announcer: Announcer,
     pub frame_size: Rect,
 }
 
@@ -24,14 +103,43 @@ impl App {
         Self {
             game: Game::default(),
             should_quit: false,
+            confirm: None,
+            confirm_selected_button: 0,
             main_menu_shown: true,
-            main_menu_selected_button: 0,
+            main_menu_selected_button: 1,
+            saved: None,
+            notice: None,
+            idle: Duration::ZERO,
+            attract: false,
             help_popup_shown: false,
+            help_scroll: 0,
+            settings_menu_shown: false,
+            settings_menu: MenuState::default(),
+            settings: Settings::default(),
             difficulty_menu_shown: false,
-            difficulty_menu_selected_button: 2,
+            setup_menu: MenuState::default(),
+            custom_menu_shown: false,
+            custom_menu: MenuState::default(),
+            custom_difficulty: DifficultyParams::default(),
+            game_mode: 0,
             difficulty: 0,
+            scoring_rule: 0,
             win_score: 10,
+            time_limit_minutes: 3,
+            server_rule: 0,
+            serve_control: 0,
+            arenas: vec![Arena::default()],
+            arena: 0,
+            left_player: 0,
+            right_player: 0,
+            handicaps: [Handicap::default(); 2],
+            names: Default::default(),
+            seed: None,
+            bot_command: None,
             game_started: false,
+            effects: Effects::default(),
+            audio: Audio::default(),
+            announcer: Announcer::default(),
             frame_size: Rect::default(),
         }
     }
@@ -40,8 +148,78 @@ impl App {
         self.game_started = true;
         self.main_menu_shown = false;
         self.difficulty_menu_shown = false;
-        self.game = Game::new(self.difficulty, self.win_score);
+        self.game = Game::new(
+            self.difficulty_params(),
+            GameMode::try_from(self.game_mode).unwrap_or(GameMode::Versus),
+            self.match_rules(),
+            self.arenas[self.arena as usize].clone(),
+        );
+        if let Some(seed) = self.seed {
+            self.game.rng = StdRng::seed_from_u64(seed);
+        }
+        for (side, player) in [
+            (Side::Left, self.left_player),
+            (Side::Right, self.right_player),
+        ] {
+            // Each computer player aims with its own seed, the way simulations do.
+            let seed = self
+                .seed
+                .map(|seed| seed.wrapping_add(side.index() as u64 + 1));
+            self.game.controllers[side.index()] = Player::try_from(player)
+                .unwrap_or(Player::Human)
+                .controller(self.bot_command.as_deref(), self.custom_difficulty, seed);
+            let name = self.names[side.index()].trim();
+            self.game.names[side.index()] = (!name.is_empty()).then(|| name.to_string());
+        }
+        self.game.full_reset();
+        self.effects.clear();
+    }
+
+    /// Starts a versus match between two computer players of random difficulty.
+    pub fn start_attract(&mut self) {
+        let mut rng = rand::thread_rng();
+        let arena = self.arenas[rng.gen_range(0..self.arenas.len())].clone();
+
+        let difficulty = Difficulty::PRESETS[rng.gen_range(0..Difficulty::PRESETS.len())];
+        self.game = Game::new(
+            difficulty.preset().unwrap_or_default(),
+            GameMode::Versus,
+            MatchRules::default(),
+            arena,
+        );
+        for side in [Side::Left, Side::Right] {
+            // Two insane players never miss, so they are left out of matches nobody is watching.
+            let difficulty = Difficulty::try_from(rng.gen_range(0..Difficulty::Insane as i8))
+                .unwrap_or(Difficulty::Easy);
+            self.game.controllers[side.index()] = Box::new(Predictor::new(
+                difficulty,
+                difficulty.preset().unwrap_or_default(),
+            ));
+        }
         self.game.full_reset();
+        self.attract = true;
+    }
+
+    pub fn stop_attract(&mut self) {
+        self.attract = false;
+        self.idle = Duration::ZERO;
+    }
+
+    pub fn match_rules(&self) -> MatchRules {
+        MatchRules {
+            scoring: ScoringRule::try_from(self.scoring_rule).unwrap_or(ScoringRule::FirstTo),
+            win_score: self.win_score,
+            time_limit: Duration::from_secs(self.time_limit_minutes as u64 * 60),
+            server: ServerRule::try_from(self.server_rule).unwrap_or(ServerRule::Random),
+            serve_control: ServeControl::try_from(self.serve_control)
+                .unwrap_or(ServeControl::Countdown),
+            handicaps: [
+                self.handicaps[0],
+                self.handicaps[1],
+                Handicap::default(),
+                Handicap::default(),
+            ],
+        }
     }
 
     pub fn update_frame_size(&mut self, _frame_size: Rect) {
@@ -51,9 +229,83 @@ impl App {
     pub fn show_main_menu(&mut self) {
         self.main_menu_shown = true;
         self.help_popup_shown = false;
+        self.settings_menu_shown = false;
         self.difficulty_menu_shown = false;
         self.game_started = false;
-        self.main_menu_selected_button = 0;
+        self.main_menu_selected_button = if self.saved.is_some() { 0 } else { 1 };
+        self.idle = Duration::ZERO;
+    }
+
+    /// Picks the saved match back up where it was left, paused.
+    pub fn continue_game(&mut self) {
+        let Some(saved) = self.saved.take() else {
+            return;
+        };
+        save::remove();
+        self.game_started = true;
+        self.main_menu_shown = false;
+        self.game = saved.game;
+        for (side, player) in [Side::Left, Side::Right].into_iter().zip(saved.players) {
+            self.game.controllers[side.index()] = Player::try_from(player)
+                .unwrap_or(Player::Human)
+                .controller(self.bot_command.as_deref(), self.custom_difficulty, None);
+        }
+        self.game.pause_game();
+        self.effects.clear();
+    }
+
+    /// Writes the match in progress to disk and quits, or stays paused with the error.
+    pub fn save_and_quit(&mut self) {
+        match save::store(&mut self.game, [self.left_player, self.right_player]) {
+            Ok(()) => self.should_quit = true,
+            Err(err) => self.notice = Some(format!("Couldn't save: {err}")),
+        }
+    }
+
+    /// Whether leaving now would throw away a match that isn't over yet.
+    pub fn is_match_in_progress(&self) -> bool {
+        self.game_started && !self.game.game_over
+    }
+
+    /// Does `confirm` right away when there is no match to lose, otherwise pauses the match and
+    /// asks first.
+    pub fn ask(&mut self, confirm: Confirm) {
+        if self.is_match_in_progress() {
+            self.game.pause_game();
+            self.confirm = Some(confirm);
+            // No is the safe answer.
+            self.confirm_selected_button = 1;
+        } else {
+            self.confirm = Some(confirm);
+            self.accept_confirm();
+        }
+    }
+
+    pub fn accept_confirm(&mut self) {
+        match self.confirm.take() {
+            Some(Confirm::Quit) => self.should_quit = true,
+            Some(Confirm::Restart) => self.show_difficulty_select_menu(),
+            Some(Confirm::MainMenu) => self.show_main_menu(),
+            None => (),
+        }
+    }
+
+    pub fn cancel_confirm(&mut self) {
+        self.confirm = None;
+    }
+
+    pub fn inc_confirm_button(&mut self) {
+        self.confirm_selected_button += 1;
+        if self.confirm_selected_button >= CONFIRM_MENU_BUTTON_COUNT as i8 {
+            self.confirm_selected_button = 0;
+        }
+    }
+
+    pub fn dec_confirm_button(&mut self) {
+        self.confirm_selected_button -= 1;
+        if self.confirm_selected_button < 0 {
+            self.confirm_selected_button = CONFIRM_MENU_BUTTON_COUNT as i8 - 1;
+        }
     }
 
     pub fn inc_main_menu_button(&mut self) {
@@ -61,11 +313,17 @@ impl App {
         if self.main_menu_selected_button >= MAIN_MENU_BUTTON_COUNT as i8 {
             self.main_menu_selected_button = 0;
         }
+        // "Continue" is only there with a saved match.
+        if self.main_menu_selected_button == 0 && self.saved.is_none() {
+            self.main_menu_selected_button = 1;
+        }
     }
 
     pub fn dec_main_menu_button(&mut self) {
         self.main_menu_selected_button -= 1;
-        if self.main_menu_selected_button < 0 {
+        if self.main_menu_selected_button < 0
+            || (self.main_menu_selected_button == 0 && self.saved.is_none())
+        {
             self.main_menu_selected_button = MAIN_MENU_BUTTON_COUNT as i8 - 1;
         }
     }
@@ -73,6 +331,7 @@ impl App {
     pub fn show_help_popup(&mut self) {
         self.main_menu_shown = true;
         self.help_popup_shown = true;
+        self.help_scroll = 0;
         self.difficulty_menu_shown = false;
         self.game_started = false;
     }
@@ -84,55 +343,85 @@ impl App {
         self.game_started = false;
     }
 
+    pub fn show_settings_menu(&mut self) {
+        self.settings_menu_shown = true;
+        self.main_menu_shown = false;
+        self.help_popup_shown = false;
+        self.settings_menu = MenuState::default();
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.settings.muted = !self.settings.muted;
+    }
+
     pub fn show_difficulty_select_menu(&mut self) {
         self.difficulty_menu_shown = true;
         self.main_menu_shown = false;
         self.help_popup_shown = false;
         self.game_started = false;
-        self.difficulty_menu_selected_button = 2;
+        self.setup_menu = MenuState {
+            selected: menu::SETUP.position("Start Game"),
+            ..MenuState::default()
+        };
+        self.game_mode = 0;
         self.difficulty = 0;
+        self.scoring_rule = 0;
         self.win_score = 10;
+        self.time_limit_minutes = 3;
+        self.server_rule = 0;
+        self.serve_control = 0;
+        self.arena = 0;
+        self.left_player = 0;
+        self.right_player = 0;
+        self.handicaps = [Handicap::default(); 2];
+        self.seed = None;
     }
 
-    pub fn inc_difficulty_menu_button(&mut self) {
-        self.difficulty_menu_selected_button += 1;
-        if self.difficulty_menu_selected_button >= DIFFICULTY_MENU_BUTTON_COUNT as i8 {
-            self.difficulty_menu_selected_button = 0;
-        }
+    /// What the difficulty picked in the setup menu sets.
+    pub fn difficulty_params(&self) -> DifficultyParams {
+        Difficulty::try_from(self.difficulty)
+            .ok()
+            .and_then(|difficulty| difficulty.preset())
+            .unwrap_or(self.custom_difficulty)
     }
 
-    pub fn dec_difficulty_menu_button(&mut self) {
-        self.difficulty_menu_selected_button -= 1;
-        if self.difficulty_menu_selected_button < 0 {
-            self.difficulty_menu_selected_button = DIFFICULTY_MENU_BUTTON_COUNT as i8 - 1;
-        }
+    pub fn show_custom_menu(&mut self) {
+        self.custom_menu_shown = true;
+        self.custom_menu = MenuState::default();
     }
 
-    pub fn inc_difficulty(&mut self) {
-        self.difficulty += 1;
-        if self.difficulty >= Difficulty::len() as i8 {
-            self.difficulty = Difficulty::len() as i8 - 1;
+    /// Goes back to the setup menu, writing the custom difficulty to the config directory. It is
+    /// kept for this session even when it can't be saved.
+    pub fn hide_custom_menu(&mut self) {
+        self.custom_menu_shown = false;
+        if let Err(err) = store_custom(&self.custom_difficulty) {
+            self.notice = Some(format!("Couldn't save the difficulty: {err}"));
         }
     }
 
-    pub fn dec_difficulty(&mut self) {
-        self.difficulty -= 1;
-        if self.difficulty < 0 {
-            self.difficulty = 0;
-        }
+    /// Opens the setup menu with both paddles handed to the computer.
+    pub fn show_watch_menu(&mut self) {
+        self.show_difficulty_select_menu();
+        self.left_player = 2 + Difficulty::Normal as i8;
+        self.right_player = 2 + Difficulty::Normal as i8;
     }
 
-    pub fn inc_win_score(&mut self) {
-        self.win_score += 1;
-        if self.win_score > 100 {
-            self.win_score = 100;
-        }
+    /// Whether the match length is picked as lives rather than a winning score.
+    pub fn has_lives(&self) -> bool {
+        self.game_mode != GameMode::Versus as i8
+    }
+
+    /// Only versus matches can be timed, the other modes go on until the lives run out.
+    pub fn is_timed_match(&self) -> bool {
+        !self.has_lives() && self.scoring_rule == ScoringRule::Timed as i8
     }
 
-    pub fn dec_win_score(&mut self) {
-        self.win_score -= 1;
-        if self.win_score < 1 {
-            self.win_score = 1;
+    /// External bots are only offered once there is a command to start them with.
+    pub fn player_count(&self) -> i8 {
+        if self.bot_command.is_some() {
+            Player::len() as i8
+        } else {
+            Player::len() as i8 - 1
         }
     }
 }
@@ -0,0 +1,543 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Arenas that ship with the game, in the order they are offered in the setup menu.
+pub const BUILT_IN: [&str; 6] = [
+    include_str!("../arenas/classic.txt"),
+    include_str!("../arenas/centre_block.txt"),
+    include_str!("../arenas/bumpers.txt"),
+    include_str!("../arenas/portals.txt"),
+    include_str!("../arenas/narrow_goals.txt"),
+    include_str!("../arenas/moving_walls.txt"),
+];
+
+/// Steps a moving block takes to travel one map cell.
+pub const MOVER_STEPS_PER_CELL: u64 = 6;
+
+/// Most map cells a level may have across and down.
+pub const MAX_MAP_SIZE: usize = 256;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Tile {
+    Empty,
+    Block,
+    /// Sends the ball straight back the way it came.
+    Bumper,
+    /// Teleports the ball to the other portal with the same letter.
+    Portal(char),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+/// A block that slides back and forth `travel` map cells from where it is drawn in the map.
+#[derive(Clone)]
+pub struct Mover {
+    pub cell: (u16, u16),
+    pub axis: Axis,
+}
+
+/// A playing field read from a level file.
+///
+/// Level files are a few `key: value` header lines followed by `map:` and the map itself, every
+/// map character covers the same fraction of the field whatever the terminal size:
+///
+/// ```text
+/// # Comments start with a hash.
+/// name: Centre Block
+/// goal: 60      percentage of every goal line that lets the ball through, defaults to 100
+/// travel: 2     map cells a moving block slides, defaults to 2
+/// map:
+/// ...o....      . empty    # block    o bumper, the middle must be empty
+/// ..A##B..      - and | blocks moving right and down, clear of the middle and the goals
+/// ...o....      A to Z portals, every letter used exactly twice
+/// ```
+///
+/// Maps are at most `MAX_MAP_SIZE` cells wide and high.
+#[derive(Clone)]
+pub struct Arena {
+    pub name: String,
+    /// Fraction of every goal line, centred, that counts as a goal.
+    pub goal: f32,
+    pub travel: u16,
+    /// Map size in cells.
+    pub size: (u16, u16),
+    pub tiles: Vec<Tile>,
+    pub movers: Vec<Mover>,
+    pub portals: HashMap<char, [(u16, u16); 2]>,
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self::parse(BUILT_IN[0]).expect("the classic arena is valid")
+    }
+}
+
+impl Arena {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut name = None;
+        let mut goal = 100;
+        let mut travel = 2;
+        let mut lines = source.lines().enumerate();
+
+        for (number, line) in lines.by_ref() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line == "map:" {
+                break;
+            }
+
+            let (key, value) = line
+                .split_once(':')
+                .ok_or(format!("line {}: expected `key: value`", number + 1))?;
+            let value = value.trim();
+            let number_value = || {
+                value
+                    .parse::<u16>()
+                    .map_err(|_| format!("line {}: `{value}` is not a number", number + 1))
+            };
+            match key.trim() {
+                "name" => name = Some(value.to_string()),
+                "goal" => goal = number_value()?,
+                "travel" => travel = number_value()?,
+                key => return Err(format!("line {}: unknown key `{key}`", number + 1)),
+            }
+        }
+
+        let name = name.ok_or("missing `name`")?;
+        if !(10..=100).contains(&goal) {
+            return Err("`goal` must be between 10 and 100".to_string());
+        }
+
+        let rows: Vec<(usize, &str)> = lines
+            .map(|(number, line)| (number, line.trim_end()))
+            .filter(|(_, line)| !line.is_empty())
+            .collect();
+        let width = rows.first().ok_or("missing map")?.1.chars().count();
+        if width > MAX_MAP_SIZE || rows.len() > MAX_MAP_SIZE {
+            return Err(format!(
+                "the map can be at most {MAX_MAP_SIZE} cells wide and high"
+            ));
+        }
+
+        let mut tiles = vec![];
+        let mut movers = vec![];
+        let mut portals: HashMap<char, Vec<(u16, u16)>> = HashMap::new();
+        for (y, (number, row)) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(format!(
+                    "line {}: map rows must be {width} wide",
+                    number + 1
+                ));
+            }
+
+            for (x, character) in row.chars().enumerate() {
+                let cell = (x as u16, y as u16);
+                tiles.push(match character {
+                    '.' | ' ' => Tile::Empty,
+                    '#' => Tile::Block,
+                    'o' => Tile::Bumper,
+                    '-' | '|' => {
+                        movers.push(Mover {
+                            cell,
+                            axis: if character == '-' {
+                                Axis::Horizontal
+                            } else {
+                                Axis::Vertical
+                            },
+                        });
+                        Tile::Empty
+                    }
+                    'A'..='Z' => {
+                        portals.entry(character).or_default().push(cell);
+                        Tile::Portal(character)
+                    }
+                    _ => {
+                        return Err(format!(
+                            "line {}: unknown map character `{character}`",
+                            number + 1
+                        ))
+                    }
+                });
+            }
+        }
+
+        // The ball is served from the middle of the field, so that has to be clear.
+        let (width, height) = (width as u16, rows.len() as u16);
+        let middle = |(x, y): (u16, u16)| {
+            ((width - 1) / 2..=width / 2).contains(&x)
+                && ((height - 1) / 2..=height / 2).contains(&y)
+        };
+        for (i, tile) in tiles.iter().enumerate() {
+            let cell = ((i % width as usize) as u16, (i / width as usize) as u16);
+            if middle(cell) && *tile != Tile::Empty {
+                return Err("the middle of the map must be empty".to_string());
+            }
+        }
+
+        // Nor may moving blocks slide over the serve or into the mouth of a goal.
+        let goal_mouth = |(x, y): (u16, u16)| {
+            let overlaps = |i: u16, length: u16| {
+                let (start, end) = (i as f32 / length as f32, (i + 1) as f32 / length as f32);
+                end > 0.5 - goal as f32 / 200.0 && start < 0.5 + goal as f32 / 200.0
+            };
+            ((x == 0 || x == width - 1) && overlaps(y, height))
+                || ((y == 0 || y == height - 1) && overlaps(x, width))
+        };
+        for mover in &movers {
+            let line = rows[mover.cell.1 as usize].0 + 1;
+            for step in 0..=travel {
+                let cell = match mover.axis {
+                    Axis::Horizontal => (
+                        mover.cell.0.saturating_add(step).min(width - 1),
+                        mover.cell.1,
+                    ),
+                    Axis::Vertical => (
+                        mover.cell.0,
+                        mover.cell.1.saturating_add(step).min(height - 1),
+                    ),
+                };
+                if middle(cell) {
+                    return Err(format!(
+                        "line {line}: a moving block slides over the middle of the map"
+                    ));
+                }
+                if goal_mouth(cell) {
+                    return Err(format!("line {line}: a moving block slides into a goal"));
+                }
+            }
+        }
+
+        let portals = portals
+            .into_iter()
+            .map(|(letter, cells)| match cells[..] {
+                [a, b] => Ok((letter, [a, b])),
+                _ => Err(format!("portal `{letter}` must appear exactly twice")),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            name,
+            goal: goal as f32 / 100.0,
+            travel,
+            size: (width, height),
+            tiles,
+            movers,
+            portals,
+        })
+    }
+
+    /// Writes the arena back out in the level format `parse` reads.
+    pub fn to_source(&self) -> String {
+        let mut source = format!(
+            "name: {}\ngoal: {}\ntravel: {}\nmap:\n",
+            self.name,
+            (self.goal * 100.0).round() as u16,
+            self.travel
+        );
+        for (i, tile) in self.tiles.iter().enumerate() {
+            let width = self.size.0 as usize;
+            let cell = ((i % width) as u16, (i / width) as u16);
+            source.push(match self.movers.iter().find(|mover| mover.cell == cell) {
+                Some(Mover {
+                    axis: Axis::Horizontal,
+                    ..
+                }) => '-',
+                Some(_) => '|',
+                None => match tile {
+                    Tile::Empty => '.',
+                    Tile::Block => '#',
+                    Tile::Bumper => 'o',
+                    Tile::Portal(letter) => *letter,
+                },
+            });
+            if cell.0 == self.size.0 - 1 {
+                source.push('\n');
+            }
+        }
+        source
+    }
+
+    /// Returns the built in arenas followed by every `.txt` level in `dir`, along with the
+    /// errors of the files that couldn't be read.
+    pub fn load_all(dir: Option<&Path>) -> (Vec<Self>, Vec<String>) {
+        let mut arenas: Vec<Self> = BUILT_IN
+            .iter()
+            .map(|source| Self::parse(source).expect("built in arenas are valid"))
+            .collect();
+        let mut errors = vec![];
+
+        let Some(entries) = dir.and_then(|dir| fs::read_dir(dir).ok()) else {
+            return (arenas, errors);
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            match fs::read_to_string(&path)
+                .map_err(|err| err.to_string())
+                .and_then(|source| Self::parse(&source))
+            {
+                Ok(arena) => arenas.push(arena),
+                Err(err) => errors.push(format!("{}: {err}", path.display())),
+            }
+        }
+
+        (arenas, errors)
+    }
+
+    /// Whether a ball reaching a goal line at `along`, a fraction of the line, scores.
+    pub fn is_goal(&self, along: f32) -> bool {
+        (along - 0.5).abs() <= self.goal / 2.0
+    }
+
+    /// Returns how far movers have slid from their map cell after `ticks` steps.
+    pub fn mover_offset(&self, ticks: u64) -> f32 {
+        if self.travel == 0 {
+            return 0.0;
+        }
+
+        let period = self.travel as u64 * MOVER_STEPS_PER_CELL * 2;
+        let phase = (ticks % period) as f32 / period as f32;
+        let triangle = 1.0 - (phase * 2.0 - 1.0).abs();
+        triangle * self.travel as f32
+    }
+
+    /// Returns the map rectangle `(x, y, width, height)` a mover covers after `ticks` steps.
+    pub fn mover_rect(&self, mover: &Mover, ticks: u64) -> (f32, f32, f32, f32) {
+        let offset = self.mover_offset(ticks);
+        let (x, y) = (mover.cell.0 as f32, mover.cell.1 as f32);
+        match mover.axis {
+            Axis::Horizontal => (x + offset, y, 1.0, 1.0),
+            Axis::Vertical => (x, y + offset, 1.0, 1.0),
+        }
+    }
+
+    pub fn tile(&self, cell: (u16, u16)) -> Tile {
+        self.tiles[cell.1 as usize * self.size.0 as usize + cell.0 as usize]
+    }
+
+    /// Converts a field cell to map coordinates.
+    pub fn to_map(&self, pos: (i16, i16), field: (u16, u16)) -> (f32, f32) {
+        (
+            pos.0 as f32 * self.size.0 as f32 / field.0 as f32,
+            pos.1 as f32 * self.size.1 as f32 / field.1 as f32,
+        )
+    }
+
+    /// Converts map coordinates to the first field cell at or after them.
+    pub fn to_field(&self, pos: (f32, f32), field: (u16, u16)) -> (u16, u16) {
+        (
+            (pos.0 * field.0 as f32 / self.size.0 as f32).ceil() as u16,
+            (pos.1 * field.1 as f32 / self.size.1 as f32).ceil() as u16,
+        )
+    }
+
+    /// Returns what is under the field cell `pos`, moving blocks included.
+    pub fn tile_at(&self, pos: (i16, i16), field: (u16, u16), ticks: u64) -> Tile {
+        if pos.0 < 0 || pos.1 < 0 || pos.0 >= field.0 as i16 || pos.1 >= field.1 as i16 {
+            return Tile::Empty;
+        }
+
+        let (x, y) = self.to_map(pos, field);
+        let moving = self.movers.iter().any(|mover| {
+            let (mx, my, mw, mh) = self.mover_rect(mover, ticks);
+            (mx..mx + mw).contains(&x) && (my..my + mh).contains(&y)
+        });
+        if moving {
+            return Tile::Block;
+        }
+
+        self.tile((
+            (x as u16).min(self.size.0 - 1),
+            (y as u16).min(self.size.1 - 1),
+        ))
+    }
+
+    /// Returns the field cell in the middle of the twin of the portal `letter` at `cell`.
+    pub fn portal_exit(&self, letter: char, pos: (i16, i16), field: (u16, u16)) -> (u16, u16) {
+        let (x, y) = self.to_map(pos, field);
+        let cell = (x as u16, y as u16);
+        let [a, b] = self.portals[&letter];
+        let exit = if a == cell { b } else { a };
+        self.to_field((exit.0 as f32 + 0.5, exit.1 as f32 + 0.5), field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A level named Test with `header` lines and the `map` rows.
+    fn level(header: &str, map: &[&str]) -> String {
+        format!("name: Test\n{header}\nmap:\n{}\n", map.join("\n"))
+    }
+
+    fn error(source: &str) -> String {
+        match Arena::parse(source) {
+            Ok(_) => panic!("parsed:\n{source}"),
+            Err(err) => err,
+        }
+    }
+
+    const OPEN: [&str; 5] = ["......", "......", "......", "......", "......"];
+
+    #[test]
+    fn built_in_arenas_parse_and_write_back() {
+        for source in BUILT_IN {
+            let arena = Arena::parse(source).unwrap();
+            let again = Arena::parse(&arena.to_source()).unwrap();
+            assert_eq!(again.to_source(), arena.to_source());
+        }
+    }
+
+    #[test]
+    fn malformed_levels_are_errors() {
+        let table = [
+            ("map:\n......\n", "missing `name`"),
+            (&level("", &[]), "missing map"),
+            (&level("goal 60", &OPEN), "line 2: expected `key: value`"),
+            (
+                &level("goal: wide", &OPEN),
+                "line 2: `wide` is not a number",
+            ),
+            (
+                &level("goal: 5", &OPEN),
+                "`goal` must be between 10 and 100",
+            ),
+            (
+                &level("goal: 101", &OPEN),
+                "`goal` must be between 10 and 100",
+            ),
+            (&level("travel: -1", &OPEN), "line 2: `-1` is not a number"),
+            (&level("size: 3", &OPEN), "line 2: unknown key `size`"),
+            (
+                &level("", &["......", "....", "......"]),
+                "line 5: map rows must be 6 wide",
+            ),
+            (
+                &level("", &["......", "..x...", "......"]),
+                "line 5: unknown map character `x`",
+            ),
+            (
+                &level("", &["......", "..#...", "......"]),
+                "the middle of the map must be empty",
+            ),
+            (
+                &level("", &[".....", "..o..", "....."]),
+                "the middle of the map must be empty",
+            ),
+            (
+                &level("", &["......", "..-...", "......"]),
+                "line 5: a moving block slides over the middle of the map",
+            ),
+        ];
+        for (source, expected) in table {
+            assert_eq!(error(source), expected, "{source}");
+        }
+    }
+
+    #[test]
+    fn maps_are_capped_in_size() {
+        let too_wide = ".".repeat(MAX_MAP_SIZE + 1);
+        let too_high = vec![".."; MAX_MAP_SIZE + 1];
+        for map in [vec![too_wide.as_str(); 3], too_high] {
+            assert_eq!(
+                error(&level("", &map)),
+                format!("the map can be at most {MAX_MAP_SIZE} cells wide and high")
+            );
+        }
+
+        // The largest map has more cells than a u16 can count.
+        let widest = ".".repeat(MAX_MAP_SIZE);
+        let arena = Arena::parse(&level("", &vec![widest.as_str(); MAX_MAP_SIZE])).unwrap();
+        assert_eq!(arena.size, (MAX_MAP_SIZE as u16, MAX_MAP_SIZE as u16));
+        let last = (MAX_MAP_SIZE as u16 - 1, MAX_MAP_SIZE as u16 - 1);
+        assert!(arena.tile(last) == Tile::Empty);
+        assert_eq!(
+            Arena::parse(&arena.to_source()).unwrap().to_source(),
+            arena.to_source()
+        );
+    }
+
+    #[test]
+    fn portals_come_in_pairs() {
+        let paired = level("", &["A....B", "......", "B....A"]);
+        let arena = Arena::parse(&paired).unwrap();
+        assert_eq!(arena.portals[&'A'], [(0, 0), (5, 2)]);
+        assert_eq!(arena.portals[&'B'], [(5, 0), (0, 2)]);
+
+        let single = level("", &["A.....", "......", "......"]);
+        assert_eq!(error(&single), "portal `A` must appear exactly twice");
+        let triple = level("", &["A....A", "......", "A....."]);
+        assert_eq!(error(&triple), "portal `A` must appear exactly twice");
+    }
+
+    #[test]
+    fn movers_keep_clear_of_the_serve_and_the_goals() {
+        let table = [
+            // Slides right over the middle.
+            (
+                "travel: 3",
+                &["......", ".-....", "......"][..],
+                "line 5: a moving block slides over the middle of the map",
+            ),
+            // Slides down over the middle.
+            (
+                "travel: 2",
+                &["......", "..|...", "......", "......", "......"],
+                "line 5: a moving block slides over the middle of the map",
+            ),
+            // Slides into the right goal.
+            (
+                "travel: 2",
+                &["......", "......", "......", "...-..", "......"],
+                "line 7: a moving block slides into a goal",
+            ),
+            // Slides off the bottom of the map onto the bottom goal.
+            (
+                "travel: 9",
+                &["......", ".|....", "......", "......", "......"],
+                "line 5: a moving block slides into a goal",
+            ),
+            // Starts in a goal.
+            (
+                "travel: 0",
+                &["......", "......", "......", "......", "-....."],
+                "line 8: a moving block slides into a goal",
+            ),
+        ];
+        for (header, map, expected) in table {
+            assert_eq!(error(&level(header, map)), expected, "{map:?}");
+        }
+
+        // Beside a narrowed goal and short of the middle.
+        let allowed = [
+            (
+                "goal: 20\ntravel: 1",
+                &["|.....", "......", "......", "......", "......", "......"][..],
+            ),
+            (
+                "travel: 1",
+                &["......", ".|....", "......", "......", "......"],
+            ),
+            (
+                "travel: 3",
+                &["......", "......", "......", "......", ".-....", "......"],
+            ),
+        ];
+        for (header, map) in allowed {
+            let source = level(header, map);
+            assert!(Arena::parse(&source).is_ok(), "{source}");
+        }
+    }
+}
@@ -0,0 +1,352 @@
+use crate::game::GameEvent;
+use crate::settings::{Settings, MAX_VOLUME};
+use std::io::{stdout, Result, Write};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Sound {
+    PaddleHit,
+    WallBounce,
+    BrickBreak,
+    Goal,
+}
+
+impl From<GameEvent> for Sound {
+    fn from(event: GameEvent) -> Self {
+        match event {
+            GameEvent::PaddleHit(..) => Self::PaddleHit,
+            GameEvent::WallBounce => Self::WallBounce,
+            GameEvent::BrickBroken(_) => Self::BrickBreak,
+            GameEvent::Goal { .. } => Self::Goal,
+        }
+    }
+}
+
+/// Something that can make a noise for a `Sound`.
+///
+/// `volume` goes from 0.0 to 1.0, backends that can't change their volume may ignore it.
+pub trait AudioBackend {
+    fn play(&mut self, sound: Sound, volume: f32) -> Result<()>;
+}
+
+/// Rings the terminal bell on goals.
+pub struct Bell;
+
+impl AudioBackend for Bell {
+    fn play(&mut self, sound: Sound, _volume: f32) -> Result<()> {
+        if sound == Sound::Goal {
+            let mut stdout = stdout();
+            stdout.write_all(b"\x07")?;
+            stdout.flush()?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Audio {
+    backend: Option<Box<dyn AudioBackend>>,
+}
+
+impl Audio {
+    pub fn new(backend: Box<dyn AudioBackend>) -> Self {
+        Self {
+            backend: Some(backend),
+        }
+    }
+
+    pub fn handle(&mut self, event: GameEvent, settings: &Settings) {
+        if settings.muted || settings.volume == 0 {
+            return;
+        }
+
+        if let Some(backend) = &mut self.backend {
+            let volume = settings.volume as f32 / MAX_VOLUME as f32;
+            // Sound is a nicety, a backend that fails once is not worth retrying every frame.
+            if backend.play(Sound::from(event), volume).is_err() {
+                self.backend = None;
+            }
+        }
+    }
+}
+
+impl Default for Audio {
+    fn default() -> Self {
+        Self::new(Box::new(Bell))
+    }
+}
+
+#[cfg(feature = "synth")]
+pub mod synth {
+    use super::{AudioBackend, Sound};
+    use std::fs::File;
+    use std::io::{Result, Seek, SeekFrom, Write};
+    use std::path::Path;
+    use std::process::{Child, Command, Stdio};
+
+    pub const SAMPLE_RATE: u32 = 22050;
+
+    /// Where synthesised samples end up.
+    pub trait SampleSink {
+        fn write_samples(&mut self, samples: &[i16]) -> Result<()>;
+    }
+
+    /// Plays square wave blips for every `Sound`.
+    pub struct Synth<S: SampleSink> {
+        sink: S,
+    }
+
+    impl<S: SampleSink> Synth<S> {
+        pub fn new(sink: S) -> Self {
+            Self { sink }
+        }
+    }
+
+    impl<S: SampleSink> AudioBackend for Synth<S> {
+        fn play(&mut self, sound: Sound, volume: f32) -> Result<()> {
+            // (frequency in Hz, length in ms) of every note of the blip.
+            let notes: &[(u32, u32)] = match sound {
+                Sound::PaddleHit => &[(440, 50)],
+                Sound::WallBounce => &[(220, 40)],
+                Sound::BrickBreak => &[(550, 30), (330, 30)],
+                Sound::Goal => &[(660, 90), (880, 150)],
+            };
+
+            let amplitude = (i16::MAX as f32 * 0.5 * volume) as i16;
+            let mut samples = vec![];
+            for (frequency, length) in notes {
+                let half_period = SAMPLE_RATE / frequency / 2;
+                for i in 0..SAMPLE_RATE * length / 1000 {
+                    samples.push(if (i / half_period).is_multiple_of(2) {
+                        amplitude
+                    } else {
+                        -amplitude
+                    });
+                }
+            }
+
+            self.sink.write_samples(&samples)
+        }
+    }
+
+    /// Appends every blip to a mono 16-bit PCM WAV file.
+    ///
+    /// The header is rewritten after each write so the file stays valid if the app is killed.
+    pub struct WavFile {
+        file: File,
+        data_len: u32,
+    }
+
+    impl WavFile {
+        pub fn create(path: &Path) -> Result<Self> {
+            let mut wav = Self {
+                file: File::create(path)?,
+                data_len: 0,
+            };
+            wav.write_header()?;
+            Ok(wav)
+        }
+
+        fn write_header(&mut self) -> Result<()> {
+            let mut header = Vec::with_capacity(44);
+            header.extend(b"RIFF");
+            header.extend((36 + self.data_len).to_le_bytes());
+            header.extend(b"WAVEfmt ");
+            header.extend(16u32.to_le_bytes());
+            header.extend(1u16.to_le_bytes());
+            header.extend(1u16.to_le_bytes());
+            header.extend(SAMPLE_RATE.to_le_bytes());
+            header.extend((SAMPLE_RATE * 2).to_le_bytes());
+            header.extend(2u16.to_le_bytes());
+            header.extend(16u16.to_le_bytes());
+            header.extend(b"data");
+            header.extend(self.data_len.to_le_bytes());
+
+            self.file.seek(SeekFrom::Start(0))?;
+            self.file.write_all(&header)?;
+            self.file.seek(SeekFrom::End(0))?;
+            Ok(())
+        }
+    }
+
+    impl SampleSink for WavFile {
+        fn write_samples(&mut self, samples: &[i16]) -> Result<()> {
+            let bytes: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+            self.file.write_all(&bytes)?;
+            self.data_len += bytes.len() as u32;
+            self.write_header()
+        }
+    }
+
+    /// Streams raw samples into the first command line player found on the system.
+    pub struct Device {
+        player: Child,
+    }
+
+    impl Device {
+        pub fn open() -> Option<Self> {
+            let rate = SAMPLE_RATE.to_string();
+            let players: [(&str, Vec<&str>); 2] = [
+                (
+                    "aplay",
+                    vec!["-q", "-t", "raw", "-f", "S16_LE", "-c", "1", "-r", &rate],
+                ),
+                (
+                    "pacat",
+                    vec!["--format=s16le", "--channels=1", "--rate", &rate],
+                ),
+            ];
+
+            players.iter().find_map(|(program, args)| {
+                Command::new(program)
+                    .args(args)
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .spawn()
+                    .ok()
+                    .map(|player| Self { player })
+            })
+        }
+    }
+
+    impl SampleSink for Device {
+        fn write_samples(&mut self, samples: &[i16]) -> Result<()> {
+            let bytes: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+            match &mut self.player.stdin {
+                Some(stdin) => stdin.write_all(&bytes),
+                None => Ok(()),
+            }
+        }
+    }
+
+    impl Drop for Device {
+        fn drop(&mut self) {
+            let _ = self.player.kill();
+            let _ = self.player.wait();
+        }
+    }
+}
+
+/// Picks the richest backend this build supports, writing to `wav_path` instead of a device when given.
+#[cfg(feature = "synth")]
+pub fn default_backend(wav_path: Option<&std::path::Path>) -> Result<Box<dyn AudioBackend>> {
+    use synth::*;
+
+    if let Some(path) = wav_path {
+        return Ok(Box::new(Synth::new(WavFile::create(path)?)));
+    }
+
+    Ok(match Device::open() {
+        Some(device) => Box::new(Synth::new(device)),
+        None => Box::new(Bell),
+    })
+}
+
+#[cfg(not(feature = "synth"))]
+pub fn default_backend(wav_path: Option<&std::path::Path>) -> Result<Box<dyn AudioBackend>> {
+    if wav_path.is_some() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "writing sounds to a WAV file needs the `synth` feature",
+        ));
+    }
+
+    Ok(Box::new(Bell))
+}
+
+#[cfg(all(test, feature = "synth"))]
+mod tests {
+    use super::synth::*;
+    use super::*;
+    use crate::game::Side;
+    use std::path::PathBuf;
+
+    /// A WAV file in the temp directory, removed again when dropped.
+    struct TempWav(PathBuf);
+
+    impl TempWav {
+        fn new(name: &str) -> Self {
+            let file = format!("pong-tui-{}-{name}.wav", std::process::id());
+            Self(std::env::temp_dir().join(file))
+        }
+
+        /// The RIFF size, the `data` size and the samples of the file.
+        fn read(&self) -> (u32, u32, Vec<i16>) {
+            let bytes = std::fs::read(&self.0).unwrap();
+            assert_eq!(&bytes[0..4], b"RIFF");
+            assert_eq!(&bytes[8..16], b"WAVEfmt ");
+            assert_eq!(&bytes[36..40], b"data");
+            let size = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+            let samples = bytes[44..]
+                .chunks_exact(2)
+                .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+                .collect();
+            (size(4), size(40), samples)
+        }
+    }
+
+    impl Drop for TempWav {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn audio(wav: &TempWav) -> Audio {
+        Audio::new(Box::new(Synth::new(WavFile::create(&wav.0).unwrap())))
+    }
+
+    const HIT: GameEvent = GameEvent::PaddleHit(Side::Left, [0.0, 0.0]);
+
+    #[test]
+    fn blip_is_written_with_valid_chunk_sizes() {
+        let wav = TempWav::new("blip");
+        let mut audio = audio(&wav);
+        audio.handle(HIT, &Settings::default());
+
+        let (riff, data, samples) = wav.read();
+        let expected = SAMPLE_RATE * 50 / 1000;
+        assert_eq!(samples.len() as u32, expected);
+        assert_eq!(data, expected * 2);
+        assert_eq!(riff, 36 + data);
+        assert!(samples.iter().any(|&sample| sample != 0));
+
+        // Every later blip is appended and the header kept up to date.
+        audio.handle(GameEvent::WallBounce, &Settings::default());
+        let (riff, data, samples) = wav.read();
+        let expected = expected + SAMPLE_RATE * 40 / 1000;
+        assert_eq!(samples.len() as u32, expected);
+        assert_eq!(data, expected * 2);
+        assert_eq!(riff, 36 + data);
+    }
+
+    #[test]
+    fn zero_volume_is_silent() {
+        let wav = TempWav::new("silent");
+        let mut synth = Synth::new(WavFile::create(&wav.0).unwrap());
+        synth.play(Sound::Goal, 0.0).unwrap();
+
+        let (_, _, samples) = wav.read();
+        assert_eq!(
+            samples.len() as u32,
+            SAMPLE_RATE * 90 / 1000 + SAMPLE_RATE * 150 / 1000
+        );
+        assert!(samples.iter().all(|&sample| sample == 0));
+    }
+
+    #[test]
+    fn muted_or_volume_zero_writes_nothing() {
+        let wav = TempWav::new("muted");
+        let mut audio = audio(&wav);
+        let muted = Settings {
+            muted: true,
+            ..Settings::default()
+        };
+        let volume_zero = Settings {
+            volume: 0,
+            ..Settings::default()
+        };
+        audio.handle(HIT, &muted);
+        audio.handle(HIT, &volume_zero);
+
+        assert_eq!(wav.read(), (36, 0, vec![]));
+    }
+}
@@ -0,0 +1,482 @@
+use crate::difficulty::{Difficulty, DifficultyParams};
+use crate::game::Side;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::env;
+use std::fmt::Display;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How long a bot in lockstep gets to answer before it is given up on.
+pub const BOT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Player {
+    Human,
+    Tracker,
+    Cpu(Difficulty),
+    /// The external bot, started with the command given with `--bot` or in the setup menu.
+    Bot,
+}
+
+impl Display for Player {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Human => write!(f, "  Human   "),
+            Self::Tracker => write!(f, " Tracker  "),
+            Self::Cpu(difficulty) => write!(f, "CPU {difficulty}"),
+            Self::Bot => write!(f, "   Bot    "),
+        }
+    }
+}
+
+impl TryFrom<i8> for Player {
+    type Error = ();
+    fn try_from(value: i8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Human),
+            1 => Ok(Self::Tracker),
+            2.. if value < 2 + Difficulty::len() as i8 => {
+                Ok(Self::Cpu(Difficulty::try_from(value - 2)?))
+            }
+            _ if value == Self::len() as i8 - 1 => Ok(Self::Bot),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Player {
+    pub fn len() -> u8 {
+        3 + Difficulty::len()
+    }
+
+    /// Returns the controller for this player, external bots are started with `bot_command` and
+    /// computer players of `Difficulty::Custom` play with the skill of `custom`. Computer players
+    /// aim with `seed` when there is one.
+    pub fn controller(
+        &self,
+        bot_command: Option<&str>,
+        custom: DifficultyParams,
+        seed: Option<u64>,
+    ) -> Box<dyn PaddleController> {
+        match *self {
+            Self::Human => Box::new(Keyboard),
+            Self::Tracker => Box::new(Tracker),
+            Self::Cpu(difficulty) => {
+                let params = difficulty.preset().unwrap_or(custom);
+                Box::new(match seed {
+                    Some(seed) => Predictor::seeded(difficulty, params, seed),
+                    None => Predictor::new(difficulty, params),
+                })
+            }
+            Self::Bot => Box::new(External::spawn(bot_command.unwrap_or_default(), false)),
+        }
+    }
+}
+
+/// Which way a controller wants its paddle to go, `Up` is towards the start of the track, so left
+/// for the top and bottom paddles.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Intent {
+    Up,
+    Down,
+    Stay,
+}
+
+/// What a controller gets to see of the match every step, positions are in cells.
+pub struct Snapshot {
+    /// The paddle being controlled.
+    pub side: Side,
+    pub tick: u64,
+    /// Width and height of the field.
+    pub field: (u16, u16),
+    pub ball: [i16; 2],
+    /// Cells the ball moves along each axis every step.
+    pub ball_vel: [i16; 2],
+    /// First cell every paddle covers along its track, indexed by `Side`.
+    pub paddles: [u16; 4],
+    /// Length of the paddle of `side`, handicaps can make it differ from the others.
+    pub paddle_length: u16,
+    /// Column of the vertical paddles and row of the horizontal ones, indexed by `Side`.
+    pub paddle_lines: [u16; 4],
+    pub scores: [u16; 4],
+}
+
+impl Snapshot {
+    /// Serialises the snapshot as a single line of JSON.
+    pub fn to_json(&self) -> String {
+        let list = |values: &[String]| values.join(",");
+        format!(
+            "{{\"side\":\"{}\",\"tick\":{},\"field\":[{},{}],\"ball\":[{},{}],\"ball_vel\":[{},{}],\"paddles\":[{}],\"paddle_length\":{},\"paddle_lines\":[{}],\"scores\":[{}]}}",
+            self.side.to_string().to_lowercase(),
+            self.tick,
+            self.field.0,
+            self.field.1,
+            self.ball[0],
+            self.ball[1],
+            self.ball_vel[0],
+            self.ball_vel[1],
+            list(&self.paddles.map(|p| p.to_string())),
+            self.paddle_length,
+            list(&self.paddle_lines.map(|p| p.to_string())),
+            list(&self.scores.map(|s| s.to_string())),
+        )
+    }
+}
+
+/// Moves a paddle, asked once every physics step.
+pub trait PaddleController {
+    fn update(&mut self, snapshot: &Snapshot) -> Intent;
+
+    /// Shown next to the paddle, `None` for human players.
+    fn name(&self) -> Option<String> {
+        None
+    }
+
+    /// Whether key presses move this paddle.
+    fn takes_keys(&self) -> bool {
+        false
+    }
+}
+
+/// A human player, the paddle only moves on key presses.
+pub struct Keyboard;
+
+impl PaddleController for Keyboard {
+    fn update(&mut self, _snapshot: &Snapshot) -> Intent {
+        Intent::Stay
+    }
+
+    fn takes_keys(&self) -> bool {
+        true
+    }
+}
+
+/// Splits the ball position and velocity into the part along the paddle track and the part across.
+fn along_across(snapshot: &Snapshot) -> (i16, i16, i16, i16, i16) {
+    let (max_x, max_y) = (snapshot.field.0 as i16 - 1, snapshot.field.1 as i16 - 1);
+    let (ball, vel) = (snapshot.ball, snapshot.ball_vel);
+    if snapshot.side.is_vertical() {
+        (ball[1], ball[0], vel[1], vel[0], max_y)
+    } else {
+        (ball[0], ball[1], vel[0], vel[1], max_x)
+    }
+}
+
+fn head_for(snapshot: &Snapshot, target: i16) -> Intent {
+    let centre = snapshot.paddles[snapshot.side.index()] as i16 + snapshot.paddle_length as i16 / 2;
+    match target.cmp(&centre) {
+        std::cmp::Ordering::Less => Intent::Up,
+        std::cmp::Ordering::Greater => Intent::Down,
+        std::cmp::Ordering::Equal => Intent::Stay,
+    }
+}
+
+/// Keeps the paddle level with the ball, wherever it is heading.
+pub struct Tracker;
+
+impl PaddleController for Tracker {
+    fn update(&mut self, snapshot: &Snapshot) -> Intent {
+        let (along, ..) = along_across(snapshot);
+        head_for(snapshot, along)
+    }
+
+    fn name(&self) -> Option<String> {
+        Some("Tracker".to_string())
+    }
+}
+
+/// A computer player that reads the ball.
+///
+/// It works out where the ball will cross its paddle line, bounces off the side walls included, and
+/// heads there. Lower difficulties move less often and aim further off.
+pub struct Predictor {
+    /// Only names the player, how it plays comes from `params`.
+    pub difficulty: Difficulty,
+    pub params: DifficultyParams,
+    /// Cells the paddle aims away from the predicted crossing, picked again every rally.
+    aim_error: i16,
+    approaching: bool,
+    rng: StdRng,
+}
+
+impl Predictor {
+    pub fn new(difficulty: Difficulty, params: DifficultyParams) -> Self {
+        Self::seeded(difficulty, params, rand::random())
+    }
+
+    pub fn seeded(difficulty: Difficulty, params: DifficultyParams, seed: u64) -> Self {
+        Self {
+            difficulty,
+            params,
+            aim_error: 0,
+            approaching: false,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl PaddleController for Predictor {
+    fn update(&mut self, snapshot: &Snapshot) -> Intent {
+        let (move_every, max_error) = self.params.ai_aim();
+        if !snapshot.tick.is_multiple_of(move_every) {
+            return Intent::Stay;
+        }
+
+        let side = snapshot.side;
+        let (along, across, vel_along, vel_across, max_along) = along_across(snapshot);
+        let line = snapshot.paddle_lines[side.index()] as i16;
+        let approaching = match side {
+            Side::Left | Side::Top => vel_across < 0,
+            Side::Right | Side::Bottom => vel_across > 0,
+        };
+
+        if approaching && !self.approaching {
+            self.aim_error = self.rng.gen_range(-max_error..=max_error);
+        }
+        self.approaching = approaching;
+
+        let target = if approaching {
+            // The ball moves one cell along for every cell across, folded back at the walls.
+            let steps = (line - across).abs() - 1;
+            let period = 2 * max_along;
+            let unfolded = (along + vel_along * steps).rem_euclid(period);
+            let crossing = if unfolded > max_along {
+                period - unfolded
+            } else {
+                unfolded
+            };
+            crossing + self.aim_error
+        } else {
+            max_along / 2
+        };
+
+        head_for(snapshot, target)
+    }
+
+    fn name(&self) -> Option<String> {
+        Some(format!("CPU {}", self.difficulty.to_string().trim()))
+    }
+}
+
+/// A bot running as a separate program, so it can be written in any language.
+///
+/// Every step the bot is sent the `Snapshot` as one line of JSON on its stdin, and it answers with
+/// lines like `{"move":"up"}`, `"down"` or `"stay"` on its stdout. Snapshots are written and
+/// answers read on separate threads so a slow bot never holds up the game: a bot that falls behind
+/// skips to the latest snapshot, and the paddle follows the latest answer it gave.
+///
+/// In lockstep every step waits for the answer instead, which headless simulations need.
+pub struct External {
+    command: String,
+    lockstep: bool,
+    process: Option<Child>,
+    outbox: Option<Arc<Outbox>>,
+    answers: Option<Receiver<Intent>>,
+    intent: Intent,
+}
+
+/// The snapshot waiting to be written to a bot, replaced by every newer one.
+#[derive(Default)]
+struct Outbox {
+    mailbox: Mutex<Mailbox>,
+    ready: Condvar,
+}
+
+#[derive(Default)]
+struct Mailbox {
+    line: Option<String>,
+    /// Set once the bot stopped reading or is being stopped.
+    closed: bool,
+}
+
+impl Outbox {
+    /// Leaves `line` for the writer in place of any snapshot it hasn't got to, returns whether
+    /// the bot is still listening.
+    fn post(&self, line: String) -> bool {
+        let Ok(mut mailbox) = self.mailbox.lock() else {
+            return false;
+        };
+        mailbox.line = Some(line);
+        self.ready.notify_one();
+        !mailbox.closed
+    }
+
+    fn close(&self) {
+        if let Ok(mut mailbox) = self.mailbox.lock() {
+            mailbox.closed = true;
+            self.ready.notify_one();
+        }
+    }
+
+    /// Waits for the next snapshot, `None` once closed.
+    fn take(&self) -> Option<String> {
+        let mut mailbox = self.mailbox.lock().ok()?;
+        loop {
+            if mailbox.closed {
+                return None;
+            }
+            if let Some(line) = mailbox.line.take() {
+                return Some(line);
+            }
+            mailbox = self.ready.wait(mailbox).ok()?;
+        }
+    }
+
+    /// Writes snapshots to `stdin` until the bot stops reading or the outbox is closed.
+    fn run_writer(&self, mut stdin: ChildStdin) {
+        while let Some(line) = self.take() {
+            if writeln!(stdin, "{line}")
+                .and_then(|()| stdin.flush())
+                .is_err()
+            {
+                break;
+            }
+        }
+        self.close();
+    }
+}
+
+impl External {
+    /// Starts `command`, split on whitespace into the program and its arguments.
+    pub fn spawn(command: &str, lockstep: bool) -> Self {
+        let mut external = Self {
+            command: command.to_string(),
+            lockstep,
+            process: None,
+            outbox: None,
+            answers: None,
+            intent: Intent::Stay,
+        };
+
+        let mut words = command.split_whitespace();
+        let Some(program) = words.next() else {
+            return external;
+        };
+        let Ok(mut child) = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            return external;
+        };
+
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return external;
+        };
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if let Some(intent) = parse_intent(&line) {
+                    if sender.send(intent).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        let outbox = Arc::new(Outbox::default());
+        let writer = Arc::clone(&outbox);
+        thread::spawn(move || writer.run_writer(stdin));
+
+        external.process = Some(child);
+        external.outbox = Some(outbox);
+        external.answers = Some(receiver);
+        external
+    }
+
+    fn stop(&mut self) {
+        if let Some(outbox) = self.outbox.take() {
+            outbox.close();
+        }
+        if let Some(mut child) = self.process.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Checks that `command` starts with a program that can be started, either a path or a name on
+/// the `PATH`. An empty command is fine, it just leaves the bot out.
+pub fn check_command(command: &str) -> Result<(), String> {
+    let Some(program) = command.split_whitespace().next() else {
+        return Ok(());
+    };
+    let found = if program.contains('/') {
+        Path::new(program).is_file()
+    } else {
+        env::var_os("PATH")
+            .is_some_and(|paths| env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+    };
+    if found {
+        Ok(())
+    } else {
+        Err(format!("`{program}` is not a program that can be started"))
+    }
+}
+
+/// Reads the move out of an answer, the JSON is only loosely checked.
+fn parse_intent(line: &str) -> Option<Intent> {
+    let value = match line.split_once(':') {
+        Some((key, value)) if key.contains("\"move\"") => value,
+        _ => line,
+    };
+    match value
+        .trim()
+        .trim_matches(|c| c == '}' || c == ' ' || c == '"')
+    {
+        "up" => Some(Intent::Up),
+        "down" => Some(Intent::Down),
+        "stay" => Some(Intent::Stay),
+        _ => None,
+    }
+}
+
+impl PaddleController for External {
+    fn update(&mut self, snapshot: &Snapshot) -> Intent {
+        // A bot that stopped listening just leaves its paddle where it is.
+        if let Some(outbox) = &self.outbox {
+            if !outbox.post(snapshot.to_json()) {
+                self.stop();
+            }
+        }
+        let Some(answers) = &self.answers else {
+            return self.intent;
+        };
+
+        if self.lockstep && self.process.is_some() {
+            match answers.recv_timeout(BOT_TIMEOUT) {
+                Ok(intent) => self.intent = intent,
+                Err(_) => self.stop(),
+            }
+        } else {
+            while let Ok(intent) = answers.try_recv() {
+                self.intent = intent;
+            }
+        }
+
+        self.intent
+    }
+
+    fn name(&self) -> Option<String> {
+        let program = self.command.split_whitespace().next().unwrap_or("Bot");
+        Some(match self.process {
+            Some(_) => format!("Bot {program}"),
+            None => format!("Bot {program} (stopped)"),
+        })
+    }
+}
+
+impl Drop for External {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
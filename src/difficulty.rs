@@ -0,0 +1,283 @@
+use crate::settings::config_dir;
+use std::fmt::Display;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+pub const MIN_BALL_SPEED: u16 = 5;
+pub const MAX_BALL_SPEED: u16 = 60;
+pub const MAX_ACCELERATION: u16 = 5;
+pub const MIN_PADDLE_LENGTH: u16 = 2;
+pub const MAX_PADDLE_LENGTH: u16 = 12;
+pub const MAX_PADDLE_SPEED: u16 = 3;
+pub const MAX_AI_SKILL: u8 = 5;
+
+/// Steps between the moves of a computer player and how far off it aims, by AI skill.
+const AI_SKILLS: [(u64, i16); MAX_AI_SKILL as usize + 1] =
+    [(4, 5), (3, 4), (2, 3), (1, 3), (1, 2), (1, 0)];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Insane,
+    /// Edited in the setup menu and kept in the config directory.
+    Custom,
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Easy => write!(f, " Easy "),
+            Self::Normal => write!(f, "Normal"),
+            Self::Hard => write!(f, " Hard "),
+            Self::Insane => write!(f, "Insane"),
+            Self::Custom => write!(f, "Custom"),
+        }
+    }
+}
+
+impl TryFrom<i8> for Difficulty {
+    type Error = ();
+    fn try_from(value: i8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Easy),
+            1 => Ok(Self::Normal),
+            2 => Ok(Self::Hard),
+            3 => Ok(Self::Insane),
+            4 => Ok(Self::Custom),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Difficulty {
+    pub const PRESETS: [Self; 4] = [Self::Easy, Self::Normal, Self::Hard, Self::Insane];
+
+    pub fn len() -> u8 {
+        5
+    }
+
+    /// Returns what the difficulty sets, `None` for `Custom`.
+    pub fn preset(&self) -> Option<DifficultyParams> {
+        let (ball_speed, paddle_length, ai_skill) = match *self {
+            Self::Easy => (10, 5, 1),
+            Self::Normal => (14, 4, 2),
+            Self::Hard => (18, 3, 3),
+            Self::Insane => (22, 2, 5),
+            Self::Custom => return None,
+        };
+        Some(DifficultyParams {
+            ball_speed,
+            acceleration: 0,
+            max_speed: 30,
+            paddle_length,
+            paddle_speed: 1,
+            ai_skill,
+        })
+    }
+}
+
+/// Everything a difficulty changes about a match.
+#[derive(Clone, Copy, PartialEq)]
+pub struct DifficultyParams {
+    /// Steps the ball takes per second when served, it moves one cell a step.
+    pub ball_speed: u16,
+    /// Steps per second the ball gains with every paddle hit.
+    pub acceleration: u16,
+    /// Steps per second the ball stops speeding up at.
+    pub max_speed: u16,
+    /// Length of the paddles in cells, caps included.
+    pub paddle_length: u16,
+    /// Cells the paddles slide per move.
+    pub paddle_speed: u16,
+    /// How well computer players of this difficulty play, from 0 to `MAX_AI_SKILL`.
+    pub ai_skill: u8,
+}
+
+impl Default for DifficultyParams {
+    fn default() -> Self {
+        Difficulty::Normal.preset().expect("normal is a preset")
+    }
+}
+
+impl DifficultyParams {
+    /// Keys of the lines `to_source` writes.
+    pub const KEYS: [&'static str; 6] = [
+        "ball_speed",
+        "acceleration",
+        "max_speed",
+        "paddle_length",
+        "paddle_speed",
+        "ai_skill",
+    ];
+
+    /// Returns the steps between the moves of a computer player and how many cells it aims
+    /// away from the ball.
+    pub fn ai_aim(&self) -> (u64, i16) {
+        AI_SKILLS[self.ai_skill.min(MAX_AI_SKILL) as usize]
+    }
+
+    /// Reads the `key: value` lines written by `to_source`.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut params = Self::default();
+        for (number, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once(':')
+                .ok_or(format!("line {}: expected `key: value`", number + 1))?;
+            let value = value.trim();
+            let number_value = |min: u16, max: u16| {
+                value
+                    .parse::<u16>()
+                    .ok()
+                    .filter(|value| (min..=max).contains(value))
+                    .ok_or(format!(
+                        "line {}: `{value}` is not a number between {min} and {max}",
+                        number + 1
+                    ))
+            };
+            match key.trim() {
+                "ball_speed" => params.ball_speed = number_value(MIN_BALL_SPEED, MAX_BALL_SPEED)?,
+                "acceleration" => params.acceleration = number_value(0, MAX_ACCELERATION)?,
+                "max_speed" => params.max_speed = number_value(MIN_BALL_SPEED, MAX_BALL_SPEED)?,
+                "paddle_length" => {
+                    params.paddle_length = number_value(MIN_PADDLE_LENGTH, MAX_PADDLE_LENGTH)?
+                }
+                "paddle_speed" => params.paddle_speed = number_value(1, MAX_PADDLE_SPEED)?,
+                "ai_skill" => params.ai_skill = number_value(0, MAX_AI_SKILL as u16)? as u8,
+                key => return Err(format!("line {}: unknown key `{key}`", number + 1)),
+            }
+        }
+        Ok(params)
+    }
+
+    pub fn to_source(self) -> String {
+        format!(
+            "ball_speed: {}\nacceleration: {}\nmax_speed: {}\npaddle_length: {}\npaddle_speed: {}\nai_skill: {}\n",
+            self.ball_speed,
+            self.acceleration,
+            self.max_speed,
+            self.paddle_length,
+            self.paddle_speed,
+            self.ai_skill
+        )
+    }
+}
+
+/// Where the custom difficulty lives, `difficulty.txt` in the config directory.
+fn custom_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("difficulty.txt"))
+}
+
+/// Reads the custom difficulty, `Ok(None)` when it was never edited.
+pub fn load_custom() -> Result<Option<DifficultyParams>, String> {
+    let Some(path) = custom_path() else {
+        return Ok(None);
+    };
+    match fs::read_to_string(path) {
+        Ok(source) => DifficultyParams::parse(&source).map(Some),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+pub fn store_custom(params: &DifficultyParams) -> Result<(), String> {
+    let path = custom_path().ok_or("no config directory to save to")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    }
+    fs::write(&path, params.to_source()).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_survive_a_round_trip() {
+        let edited = DifficultyParams {
+            ball_speed: MAX_BALL_SPEED,
+            acceleration: MAX_ACCELERATION,
+            max_speed: MIN_BALL_SPEED,
+            paddle_length: MIN_PADDLE_LENGTH,
+            paddle_speed: MAX_PADDLE_SPEED,
+            ai_skill: 0,
+        };
+        let presets = Difficulty::PRESETS.map(|difficulty| difficulty.preset().unwrap());
+        for params in presets.into_iter().chain([edited]) {
+            let source = params.to_source();
+            assert!(DifficultyParams::parse(&source) == Ok(params), "{source}");
+            for key in DifficultyParams::KEYS {
+                assert!(source.contains(&format!("{key}: ")), "{key}");
+            }
+        }
+    }
+
+    #[test]
+    fn missing_keys_keep_the_normal_preset() {
+        let normal = Difficulty::Normal.preset().unwrap();
+        assert!(DifficultyParams::parse("") == Ok(normal));
+        assert!(DifficultyParams::parse("# nothing edited yet\n\n") == Ok(normal));
+
+        let params = DifficultyParams::parse("ball_speed: 40\nai_skill: 0\n").unwrap();
+        assert_eq!((params.ball_speed, params.ai_skill), (40, 0));
+        assert_eq!(params.paddle_length, normal.paddle_length);
+        assert_eq!(params.max_speed, normal.max_speed);
+    }
+
+    #[test]
+    fn unknown_keys_and_bad_values_are_errors() {
+        let table = [
+            ("speed: 10", "line 1: unknown key `speed`"),
+            (
+                "ball_speed: 10\nBall_Speed: 10",
+                "line 2: unknown key `Ball_Speed`",
+            ),
+            ("ball_speed 10", "line 1: expected `key: value`"),
+            (
+                "ball_speed: 4",
+                "line 1: `4` is not a number between 5 and 60",
+            ),
+            (
+                "ball_speed: 61",
+                "line 1: `61` is not a number between 5 and 60",
+            ),
+            (
+                "acceleration: 6",
+                "line 1: `6` is not a number between 0 and 5",
+            ),
+            (
+                "max_speed: fast",
+                "line 1: `fast` is not a number between 5 and 60",
+            ),
+            (
+                "paddle_length: 1",
+                "line 1: `1` is not a number between 2 and 12",
+            ),
+            (
+                "paddle_speed: 0",
+                "line 1: `0` is not a number between 1 and 3",
+            ),
+            (
+                "ai_skill: -1",
+                "line 1: `-1` is not a number between 0 and 5",
+            ),
+            (
+                "ai_skill: 256",
+                "line 1: `256` is not a number between 0 and 5",
+            ),
+        ];
+        for (source, expected) in table {
+            match DifficultyParams::parse(source) {
+                Ok(_) => panic!("parsed `{source}`"),
+                Err(err) => assert_eq!(err, expected),
+            }
+        }
+    }
+}
@@ -1,66 +1,181 @@
+use crate::announce::{self, TextScreen};
 use crate::app::*;
-use crate::font;
+use crate::arena::Tile;
+use crate::effects::*;
+use crate::font::{self, BigText, FontStyle};
 use crate::game::*;
+use crate::keymap::{self, Screen};
+use crate::menu::{self, ItemKind, Menu};
+use crate::rules::*;
+use crate::settings::Settings;
+use crate::theme::{ColorDepth, Palette};
 use ratatui::{
     layout::*,
     style::*,
-    text::Line,
+    text::{Line, Span},
     widgets::{block::*, *},
     Frame,
 };
 
-pub fn ui(frame: &mut Frame, app: &mut App, update_ball: bool) {
+pub fn ui(frame: &mut Frame, app: &mut App) {
     app.update_frame_size(frame.size());
 
+    if app.settings.text_mode && Screen::current(app) != Screen::Help {
+        if let Some(screen) = announce::describe(app) {
+            render_text_screen(frame, &screen);
+            render_footer(frame, app);
+            adapt_colors(frame, app.settings.color_depth);
+            return;
+        }
+    }
+
     if app.main_menu_shown {
+        if app.attract {
+            render_attract(frame, app);
+        }
         render_main_menu(frame, app);
+    } else if app.settings_menu_shown {
+        render_settings_menu(frame, app);
+    } else if app.custom_menu_shown {
+        render_custom_menu(frame, app);
     } else if app.difficulty_menu_shown {
         render_difficulty_select_menu(frame, app);
     } else if app.game_started {
-        render_game(frame, app, update_ball);
+        render_game(frame, app);
+    }
+
+    if let Some(confirm) = app.confirm {
+        render_confirm_popup(frame, app, confirm);
     }
+    render_footer(frame, app);
+    adapt_colors(frame, app.settings.color_depth);
+}
+
+/// Brings every colour drawn down to what the terminal can show. Without colours, cells with a
+/// background are shown reversed so walls, paddles and popups stay visible.
+fn adapt_colors(frame: &mut Frame, depth: ColorDepth) {
+    if depth == ColorDepth::TrueColor {
+        return;
+    }
+    for cell in frame.buffer_mut().content.iter_mut() {
+        if depth == ColorDepth::Mono && cell.bg != Color::Reset {
+            cell.modifier.toggle(Modifier::REVERSED);
+        }
+        cell.fg = depth.adapt(cell.fg);
+        cell.bg = depth.adapt(cell.bg);
+    }
+}
+
+/// Draws `screen` as plain lines from the top left, the focused item marked with `>`.
+fn render_text_screen(frame: &mut Frame, screen: &TextScreen) {
+    let frame_size = frame.size();
+
+    let mut lines = vec![screen.title.clone()];
+    lines.extend(screen.notice.clone());
+    lines.push(String::new());
+    if !screen.info.is_empty() {
+        lines.extend(screen.info.iter().cloned());
+        lines.push(String::new());
+    }
+    let first_item = lines.len();
+    for (i, item) in screen.items.iter().enumerate() {
+        let marker = if screen.selected == Some(i) { ">" } else { " " };
+        lines.push(format!("{marker} {item}"));
+    }
+
+    // The footer takes the last line, the rest scrolls to keep the focused item in view.
+    let height = frame_size.height.saturating_sub(1);
+    let focused = first_item + screen.selected.unwrap_or(0);
+    let scroll = (focused + 1).saturating_sub(height as usize);
+    let text: Vec<Line<'_>> = lines.into_iter().skip(scroll).map(Line::from).collect();
+    frame.render_widget(
+        Paragraph::new(text).white(),
+        Rect::new(1, 0, frame_size.width.saturating_sub(1), height),
+    );
+}
+
+/// Draws the computer match playing behind the main menu, dimmed so the menu stays readable.
+fn render_attract(frame: &mut Frame, app: &mut App) {
+    render_game(frame, app);
+
+    let area = frame.size();
+    frame.buffer_mut().set_style(
+        area,
+        Style::new()
+            .fg(app.settings.palette().label)
+            .bg(Color::Reset),
+    );
 }
 
 fn render_main_menu(frame: &mut Frame, app: &mut App) {
     let frame_size = frame.size();
-    const BUTTONS: [&str; MAIN_MENU_BUTTON_COUNT] = ["New Game", "Help", "Quit"];
+    let palette = app.settings.palette();
+    let glyphs = app.settings.glyphs();
+    const BUTTONS: [&str; MAIN_MENU_BUTTON_COUNT] = MAIN_MENU_BUTTONS;
+
+    let font = FontStyle::Auto.font(
+        frame_size.height,
+        frame_size.width.saturating_sub(4),
+        "PONG-TUI",
+        app.settings.ascii,
+    );
 
     let ui_layout = Layout::new(
         Direction::Vertical,
         [
             Constraint::Ratio(1, 5),
-            Constraint::Max(13),
+            // The logo with its frame, the padding and the caption.
+            Constraint::Max(font.height + 5),
             Constraint::Max(2),
-            Constraint::Max(2 * BUTTONS.len() as u16),
+            Constraint::Max(2 * (BUTTONS.len() as u16 - app.saved.is_none() as u16)),
             Constraint::Min(1),
         ],
     )
     .split(frame_size);
 
-    frame.render_widget(
-        Paragraph::new(font::PONG_TUI)
-            .alignment(Alignment::Center)
-            .block(
-                Block::default()
-                    .padding(Padding::vertical(1))
-                    .title("Made with Rust 🦀")
-                    .title_style(Style::new().yellow().bold().slow_blink())
-                    .title_position(Position::Bottom)
-                    .title_alignment(Alignment::Center),
-            )
-            .white(),
-        ui_layout[1],
-    );
+    let title_block = Block::default()
+        .padding(Padding::vertical(1))
+        .title(if app.settings.ascii {
+            "Made with Rust"
+        } else {
+            "Made with Rust 🦀"
+        })
+        .title_style(blink(app, Style::new().fg(palette.accent).bold()))
+        .title_position(Position::Bottom)
+        .title_alignment(Alignment::Center);
+    let title_area = title_block.inner(ui_layout[1]);
+    frame.render_widget(title_block, ui_layout[1]);
+
+    let title = BigText::new("PONG-TUI", font)
+        .style(Style::new().white())
+        .alignment(Alignment::Center);
+    // One column more than the text, to balance the gap glyphs leave on their left.
+    let logo_area = Rect::new(
+        title_area.x + title_area.width.saturating_sub(title.width() + 3) / 2,
+        title_area.y,
+        title.width() + 3,
+        font.height + 2,
+    )
+    .intersection(title_area);
+    let logo_block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(glyphs.frame)
+        .white();
+    frame.render_widget(title, logo_block.inner(logo_area));
+    frame.render_widget(logo_block, logo_area);
 
     let mut text: Vec<Line<'_>> = vec![];
-    for i in 0..BUTTONS.len() {
+    for (i, name) in BUTTONS.iter().enumerate() {
+        if i == 0 && app.saved.is_none() {
+            continue;
+        }
         let mut button = if app.main_menu_selected_button == i as i8 {
             "> ".to_string()
         } else {
             "".to_string()
         };
 
-        button.push_str(BUTTONS[i]);
+        button.push_str(name);
 
         if app.main_menu_selected_button == i as i8 {
             button.push_str(" <");
@@ -74,140 +189,262 @@ fn render_main_menu(frame: &mut Frame, app: &mut App) {
         ui_layout[3],
     );
 
+    if let Some(notice) = &app.notice {
+        frame.render_widget(
+            Paragraph::new(notice.as_str())
+                .alignment(Alignment::Center)
+                .fg(app.settings.palette().accent),
+            ui_layout[2],
+        );
+    }
+
     if app.help_popup_shown {
-        show_help_popup(frame);
+        show_help_popup(frame, app);
     }
 }
 
+fn render_settings_menu(frame: &mut Frame, app: &mut App) {
+    render_menu(frame, app, &menu::SETTINGS, None);
+}
+
+fn render_custom_menu(frame: &mut Frame, app: &mut App) {
+    render_menu(
+        frame,
+        app,
+        &menu::CUSTOM_DIFFICULTY,
+        Some("Custom Difficulty"),
+    );
+}
+
 fn render_difficulty_select_menu(frame: &mut Frame, app: &mut App) {
-    let frame_size = frame.size();
-    const BUTTONS: [&str; DIFFICULTY_MENU_BUTTON_COUNT - DIFFICULTY_MENU_CHOICE_COUNT] =
-        ["Start Game", "Back"];
-    const CHOICES: [&str; DIFFICULTY_MENU_CHOICE_COUNT] =
-        ["Select Difficulty:", "Select Win Score:"];
+    render_menu(frame, app, &menu::SETUP, None);
+}
 
-    let layout_vert = Layout::new(
-        Direction::Vertical,
-        [
-            Constraint::Ratio(1, 4),
-            Constraint::Ratio(1, 2),
-            Constraint::Ratio(1, 4),
-        ],
-    )
-    .split(frame_size);
+/// Draws `menu` in the middle of the screen under `title`, with the notice below it.
+///
+/// Rows are spaced out when the screen is tall enough for it, and when even packed rows don't fit
+/// the menu scrolls to keep the selected item in view.
+fn render_menu(frame: &mut Frame, app: &mut App, menu: &Menu, title: Option<&str>) {
+    let frame_size = frame.size();
+    let title_height = if title.is_some() { 2 } else { 0 };
+    let notice_height = if app.notice.is_some() { 3 } else { 0 };
+    // The footer takes the last line.
+    let height = frame_size
+        .height
+        .saturating_sub(1 + title_height + notice_height);
+
+    let shown = menu.shown(app);
+    let selected = menu.selected(app);
+    let rows = shown.len() as u16;
+    let row_height = if rows * 2 <= height { 2 } else { 1 };
+    let scrolls = rows * row_height > height;
+    // Scrolling takes a line above and below the items for the arrows.
+    let visible = if scrolls {
+        height.saturating_sub(2).max(1) as usize
+    } else {
+        shown.len()
+    };
+    let position = shown.iter().position(|&i| i == selected).unwrap_or(0);
+    let state = (menu.state_mut)(app);
+    state.selected = selected;
+    state.scroll = state
+        .scroll
+        .min(position)
+        .max((position + 1).saturating_sub(visible))
+        .min(shown.len().saturating_sub(visible));
+    let scroll = state.scroll;
+
+    let menu_height = if scrolls { height } else { rows * row_height };
+    let top = title_height + height.saturating_sub(menu_height) / 2;
+    let menu_area = Rect::new(frame_size.width / 4, top, frame_size.width / 2, menu_height)
+        .intersection(frame_size);
+
+    let glyphs = app.settings.glyphs();
+    let mut lines: Vec<Line<'_>> = vec![];
+    if scrolls {
+        let arrow = if scroll > 0 { glyphs.more_above } else { "" };
+        lines.push(Line::from(arrow).alignment(Alignment::Center));
+    }
+    for &i in shown.iter().skip(scroll).take(visible) {
+        lines.push(menu_line(
+            app,
+            menu,
+            i,
+            i == selected,
+            menu_area.width as usize,
+        ));
+        if row_height == 2 {
+            lines.push(Line::default());
+        }
+    }
+    if scrolls && scroll + visible < shown.len() {
+        lines.push(Line::from(glyphs.more_below).alignment(Alignment::Center));
+    }
+    frame.render_widget(Paragraph::new(lines).white(), menu_area);
 
-    let layout_horiz = Layout::new(
-        Direction::Horizontal,
-        [
-            Constraint::Ratio(1, 4),
-            Constraint::Ratio(1, 2),
-            Constraint::Ratio(1, 4),
-        ],
-    )
-    .split(layout_vert[1]);
+    if let Some(title) = title {
+        frame.render_widget(
+            Paragraph::new(title)
+                .alignment(Alignment::Center)
+                .fg(app.settings.palette().accent)
+                .bold(),
+            Rect::new(menu_area.x, top.saturating_sub(2), menu_area.width, 1),
+        );
+    }
+    if let Some(notice) = &app.notice {
+        frame.render_widget(
+            Paragraph::new(notice.as_str())
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true })
+                .fg(app.settings.palette().accent),
+            Rect::new(menu_area.x, menu_area.bottom() + 1, menu_area.width, 2)
+                .intersection(frame_size),
+        );
+    }
+}
 
-    let ui_layout = Layout::new(
-        Direction::Vertical,
-        (0..DIFFICULTY_MENU_CHOICE_COUNT)
-            .map(|_| Constraint::Min(2))
-            .chain([Constraint::Min(1)]),
-    )
-    .split(layout_horiz[1]);
+/// Lays out one item of a menu `width` columns wide, the label on the left and the value on the
+/// right between the arrows it can still move in.
+fn menu_line(app: &App, menu: &Menu, index: usize, selected: bool, width: usize) -> Line<'static> {
+    let item = &menu.items[index];
+    if let ItemKind::Heading = item.kind {
+        return Line::styled(item.label, Style::new().bold().underlined())
+            .alignment(Alignment::Center);
+    }
+    let Some(value) = item.value(app) else {
+        return Line::from(if selected {
+            format!("> {} <", item.label)
+        } else {
+            item.label.to_string()
+        });
+    };
 
-    let button_layout = Layout::new(
-        Direction::Vertical,
-        (0..(DIFFICULTY_MENU_BUTTON_COUNT - DIFFICULTY_MENU_CHOICE_COUNT))
-            .map(|_| Constraint::Max(2)),
-    )
-    .split(ui_layout[DIFFICULTY_MENU_CHOICE_COUNT]);
+    let label_width = item.label.chars().count();
+    // Room the label leaves, with a column between them.
+    let room = width.saturating_sub(label_width + 1);
+    if let (Some(input), true) = (&(menu.state)(app).editing, selected) {
+        let (text, cursor) = input.view(room.saturating_sub(2));
+        let shown = text.chars().count().max(cursor + 1);
+        let under = text.chars().nth(cursor).unwrap_or(' ');
+        return Line::from(vec![
+            Span::raw(format!(
+                "{}{}",
+                item.label,
+                " ".repeat(width.saturating_sub(label_width + shown + 2))
+            )),
+            Span::raw(text.chars().take(cursor).collect::<String>()),
+            Span::styled(under.to_string(), Style::new().reversed()),
+            Span::raw(format!(
+                "{}  ",
+                text.chars().skip(cursor + 1).collect::<String>()
+            )),
+        ]);
+    }
 
-    let mut choice_widget =
-        |layout_index: u8, name: &str, value: &str, i: i8, left_bound: bool, right_bound: bool| {
-            frame.render_widget(
-                Paragraph::new(name).alignment(Alignment::Left).white(),
-                ui_layout[layout_index as usize],
-            );
+    let (left, right) = if selected {
+        item.bounds(app)
+    } else {
+        (false, false)
+    };
+    let value = format!(
+        "{}{value:^value_width$}{}",
+        if left { "< " } else { "  " },
+        if right { " >" } else { "  " },
+        value_width = item.value_width(app),
+    );
 
-            let mut widget_text = if app.difficulty_menu_selected_button == i && left_bound {
-                "< ".to_string()
-            } else {
-                "  ".to_string()
-            };
-            widget_text.push_str(value);
-            widget_text.push_str(if app.difficulty_menu_selected_button == i && right_bound {
-                " >"
-            } else {
-                "  "
-            });
+    // Text longer than the room shows its end.
+    let length = value.chars().count();
+    let value = if length > room {
+        let tail: String = value.chars().skip(length - room + 1).collect();
+        format!("{}{tail}", app.settings.glyphs().ellipsis)
+    } else {
+        value
+    };
+    let gap = width.saturating_sub(label_width + value.chars().count());
+    Line::from(format!("{}{}{value}", item.label, " ".repeat(gap)))
+}
 
-            frame.render_widget(
-                Paragraph::new(widget_text)
-                    .alignment(Alignment::Right)
-                    .white(),
-                ui_layout[layout_index as usize],
-            );
-        };
+fn render_game(frame: &mut Frame, app: &mut App) {
+    let frame_size = frame.size();
+    app.game.resize(frame_size.width, frame_size.height);
+    // The match waits for the terminal to grow back, like `Game::step` does.
+    if frame_size.width < MIN_FIELD || frame_size.height < MIN_FIELD {
+        frame.render_widget(
+            Paragraph::new("Terminal too small").wrap(Wrap { trim: true }),
+            frame_size,
+        );
+        return;
+    }
 
-    choice_widget(
-        0,
-        CHOICES[0],
-        Difficulty::try_from(app.difficulty)
-            .unwrap_or(Difficulty::Insane)
-            .to_string()
-            .as_str(),
-        0,
-        app.difficulty > 0,
-        app.difficulty < Difficulty::len() as i8 - 1,
-    );
+    match app.game.mode {
+        GameMode::Versus => render_versus_scores(frame, app),
+        GameMode::FourPlayer => render_four_player_scores(frame, app),
+        GameMode::Practice | GameMode::Breakout => render_solo_scores(frame, app),
+    }
 
-    choice_widget(
-        1,
-        CHOICES[1],
-        format!("{: >5}  ", app.win_score.to_string()).as_str(),
-        1,
-        app.win_score > 1,
-        app.win_score < 100,
-    );
+    let palette = app.settings.palette();
+    render_arena(frame, &app.game, &app.settings);
+    render_bricks(frame, &app.game, &app.settings);
 
-    let mut button_widget =
-        |layout_index: usize, name: &str, alignment: Alignment, selected: bool| {
-            let mut button = if selected {
-                "> ".to_string()
-            } else {
-                "".to_string()
-            };
-            button.push_str(name);
-            if selected {
-                button.push_str(" <");
+    // Rendering the platforms.
+    for side in Side::ALL {
+        if app.game.is_wall(side) {
+            // Only four player matches draw the top and bottom walls, like the classic game.
+            if app.game.mode == GameMode::FourPlayer || side.is_vertical() {
+                render_wall(frame, side, palette);
             }
+        } else {
+            render_platform(frame, &app.game, side, &app.settings);
+        }
+    }
 
-            frame.render_widget(
-                Paragraph::new(vec![Line::default(), Line::from(button)])
-                    .alignment(alignment)
-                    .white(),
-                button_layout[layout_index],
-            );
-        };
-
-    button_widget(
-        0,
-        BUTTONS[0],
-        Alignment::Center,
-        app.difficulty_menu_selected_button == DIFFICULTY_MENU_CHOICE_COUNT as i8,
-    );
+    // Rendering the BALL.
+    let b_pos = [
+        (app.game.b_pos[0] * (frame_size.width - 1) as f32).round() as u16,
+        (app.game.b_pos[1] * (frame_size.height - 1) as f32).round() as u16,
+    ];
+    render_trail(frame, app);
+    let b_area = Rect::new(b_pos[0], b_pos[1], 1, 1);
+    let ball = if app.settings.large_glyphs {
+        app.settings.glyphs().large_ball
+    } else {
+        app.settings.glyphs().ball
+    };
+    frame.render_widget(Paragraph::new(ball).fg(palette.ball), b_area);
+    render_cpu_labels(frame, &app.game, palette);
+    render_particles(frame, &app.effects, &app.settings);
+    render_edge_flash(frame, &app.effects, palette);
+    shake_frame(frame, app.effects.shake_offset());
 
-    button_widget(
-        1,
-        BUTTONS[1],
-        Alignment::Left,
-        app.difficulty_menu_selected_button == DIFFICULTY_MENU_CHOICE_COUNT as i8 + 1,
-    );
+    if app.game.game_paused {
+        add_pause_menu(frame, app);
+    } else if app.game.game_over {
+        add_game_over_menu(frame, app);
+    } else if !app.game.is_ball_in_play() {
+        add_serve_overlay(frame, app);
+    }
 }
 
-fn render_game(frame: &mut Frame, app: &mut App, update_ball: bool) {
+fn render_versus_scores(frame: &mut Frame, app: &mut App) {
     let frame_size = frame.size();
-    let split = 15;
-    app.frame_size = frame_size;
+    let timed = app.game.rules.scoring == ScoringRule::Timed;
+    let (l_text, r_text) = (
+        app.game.score(Side::Left).to_string(),
+        app.game.score(Side::Right).to_string(),
+    );
+    let hud = format!("{l_text}{r_text}{}", if timed { "00:00" } else { "" });
+    let font = app.settings.font_style.font(
+        frame_size.height,
+        frame_size.width.saturating_sub(if timed { 26 } else { 16 }),
+        &hud,
+        app.settings.ascii,
+    );
+    let split = if timed {
+        font.text_width("00:00") + 10
+    } else {
+        15
+    };
 
     //Rendering the UI.
     let ui_layout = Layout::new(
@@ -220,159 +457,494 @@ fn render_game(frame: &mut Frame, app: &mut App, update_ball: bool) {
     )
     .split(frame_size);
 
-    let mut l_text: Vec<Paragraph> = vec![];
-    let mut l_score = app.game.l_score;
-    loop {
-        if l_score == 0 {
-            if l_text.len() == 0 {
-                l_text.push(
-                    Paragraph::new(String::from(format!("{}", font::NUMS[0])))
-                        .alignment(Alignment::Right),
-                );
-            }
-            break;
-        }
-        let num = l_score % 10;
-        l_text.push(
-            Paragraph::new(String::from(format!("{}", font::NUMS[num as usize])))
-                .alignment(Alignment::Right),
-        );
-        l_score = (l_score as f32 / 10.0) as u16;
+    // The left score ends and the right score starts a few cells inside the middle column.
+    let l_x = (ui_layout[1].x + 3).saturating_sub(font.text_width(&l_text));
+    let r_x = ui_layout[2].x.saturating_sub(4);
+    frame.render_widget(
+        BigText::new(&l_text, font).style(score_style(app, Side::Left)),
+        Rect::new(l_x, 0, font.text_width(&l_text), font.height),
+    );
+    frame.render_widget(
+        BigText::new(&r_text, font).style(score_style(app, Side::Right)),
+        Rect::new(r_x, 0, font.text_width(&r_text), font.height),
+    );
+
+    let mut mid: Vec<Line<'_>> = vec![];
+    for _ in 0..frame_size.height {
+        mid.push(Line::from(app.settings.glyphs().net));
     }
+    frame.render_widget(
+        Paragraph::new(mid).alignment(Alignment::Center).white(),
+        ui_layout[1],
+    );
 
-    l_text.reverse();
-    let mut l_area = Rect::new(ui_layout[0].width - 3, 0, 6, 5);
-    loop {
-        if l_text.len() == 0 {
-            break;
-        }
-        let character = l_text.pop().unwrap();
-        frame.render_widget(character.white(), l_area);
-        l_area.x -= 6;
-    }
-
-    let mut r_text: Vec<Paragraph> = vec![];
-    let mut r_score = app.game.r_score;
-    loop {
-        if r_score == 0 {
-            if r_text.len() == 0 {
-                r_text.push(
-                    Paragraph::new(String::from(format!("{}", font::NUMS[0])))
-                        .alignment(Alignment::Right),
-                );
-            }
-            break;
-        }
-        let num = r_score % 10;
-        r_text.push(
-            Paragraph::new(String::from(format!("{}", font::NUMS[num as usize])))
-                .alignment(Alignment::Right),
-        );
-        r_score = (r_score as f32 / 10.0) as u16;
+    if timed {
+        render_clock(frame, app, ui_layout[1], font);
     }
 
-    let mut r_area = Rect::new(ui_layout[2].x - 4, 0, 6, 5);
-    loop {
-        if r_text.len() == 0 {
-            break;
+    let rules = app.game.rules;
+    let (l_score, r_score) = (app.game.score(Side::Left), app.game.score(Side::Right));
+    let status = if rules.is_sudden_death(l_score, r_score, app.game.elapsed) {
+        "GOLDEN POINT"
+    } else if rules.is_deuce(l_score, r_score) {
+        "DEUCE"
+    } else {
+        match rules.advantage(l_score, r_score) {
+            Some(Side::Left) => "< ADVANTAGE",
+            Some(_) => "ADVANTAGE >",
+            None => "",
         }
-        let character = r_text.pop().unwrap();
-        frame.render_widget(character.white(), r_area);
-        r_area.x += 6;
+    };
+    if !status.is_empty() {
+        let status_area = Rect::new(ui_layout[1].x, font.height + 1, ui_layout[1].width, 1);
+        frame.render_widget(Clear, status_area);
+        frame.render_widget(
+            Paragraph::new(status)
+                .alignment(Alignment::Center)
+                .fg(app.settings.palette().accent)
+                .bold(),
+            status_area,
+        );
     }
+}
 
-    let mut mid: Vec<Line<'_>> = vec![];
-    for _ in 0..frame_size.height {
-        mid.push(Line::from(font::LINE));
+fn render_four_player_scores(frame: &mut Frame, app: &mut App) {
+    let frame_size = frame.size();
+
+    for side in Side::ALL {
+        let lives = app.game.lives[side.index()];
+        let text = if lives == 0 {
+            format!("{side} is out")
+        } else {
+            format!(
+                "{side} {}  {}{lives}",
+                app.game.score(side),
+                app.settings.glyphs().lives
+            )
+        };
+        let area = match side {
+            Side::Left => Rect::new(3, frame_size.height / 2, frame_size.width / 3, 1),
+            Side::Right => Rect::new(
+                frame_size.width.saturating_sub(3 + frame_size.width / 3),
+                frame_size.height / 2,
+                frame_size.width / 3,
+                1,
+            ),
+            Side::Top => Rect::new(0, 3, frame_size.width, 1),
+            Side::Bottom => Rect::new(0, frame_size.height.saturating_sub(4), frame_size.width, 1),
+        };
+        let alignment = match side {
+            Side::Left => Alignment::Left,
+            Side::Right => Alignment::Right,
+            Side::Top | Side::Bottom => Alignment::Center,
+        };
+        let style = if app.effects.is_pulsing(side) {
+            score_style(app, side)
+        } else {
+            Style::new().fg(app.settings.palette().label)
+        };
+
+        frame.render_widget(
+            Paragraph::new(text).alignment(alignment).style(style),
+            area.intersection(frame_size),
+        );
     }
+}
+
+fn render_solo_scores(frame: &mut Frame, app: &mut App) {
+    let frame_size = frame.size();
+    let game = &app.game;
+    let elapsed = game.elapsed.as_secs();
+
+    let text = format!(
+        "{}  Streak {} (best {})  {}{}  {}:{:02}",
+        game.score(Side::Left),
+        game.streak,
+        game.best_streak,
+        app.settings.glyphs().lives,
+        game.lives[Side::Left.index()],
+        elapsed / 60,
+        elapsed % 60,
+    );
+    let style = if app.effects.is_pulsing(Side::Left) {
+        score_style(app, Side::Left)
+    } else {
+        Style::new().fg(app.settings.palette().label)
+    };
+
+    // Breakout keeps the score clear of the bricks.
+    let width = if game.mode == GameMode::Breakout {
+        (frame_size.width as f32 * BRICKS_LEFT) as u16
+    } else {
+        frame_size.width
+    };
     frame.render_widget(
-        Paragraph::new(mid).alignment(Alignment::Center).white(),
-        ui_layout[1],
+        Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .style(style),
+        Rect::new(0, 1, width, 1),
     );
+}
 
-    // Rendering the platforms.
-    let platform_height = 3 - app.game.difficulty as u16;
+fn render_platform(frame: &mut Frame, game: &Game, side: Side, settings: &Settings) {
+    let length = game.paddle_length(side);
+    let start = game.paddle_start(side);
+    let line = game.paddle_line(side);
+    let color = settings.palette().paddle;
+    let glyphs = settings.glyphs();
+
+    // Large paddles are solid and take the goal line behind them as well.
+    if settings.large_glyphs {
+        let outer = match side {
+            Side::Left | Side::Top => line.saturating_sub(1),
+            Side::Right | Side::Bottom => line + 1,
+        };
+        let area = if side.is_vertical() {
+            Rect::new(line.min(outer), start, 2, length)
+        } else {
+            Rect::new(start, line.min(outer), length, 2)
+        };
+        frame.render_widget(Block::new().bg(color), area.intersection(frame.size()));
+        return;
+    }
 
-    let mut l_platform_widget: Vec<Line> = vec![Line::from("▟")];
-    if platform_height > 0 {
-        for _ in 0..platform_height {
-            l_platform_widget.push(Line::from(font::PLAT));
+    let (first, last) = match side {
+        Side::Left => glyphs.paddle_caps[0],
+        Side::Right => glyphs.paddle_caps[1],
+        Side::Top | Side::Bottom => glyphs.paddle_caps[2],
+    };
+
+    if side.is_vertical() {
+        let mut platform_widget: Vec<Line> = vec![Line::from(first)];
+        for _ in 0..length.saturating_sub(2) {
+            platform_widget.push(Line::from(glyphs.paddle));
         }
+        platform_widget.push(Line::from(last));
+
+        let area = Rect::new(line, start, 1, length);
+        frame.render_widget(Paragraph::new(platform_widget).fg(color), area);
+    } else {
+        let platform_widget = format!(
+            "{first}{}{last}",
+            glyphs.paddle.repeat(length.saturating_sub(2) as usize)
+        );
+
+        let area = Rect::new(start, line, length, 1);
+        frame.render_widget(Paragraph::new(platform_widget).fg(color), area);
     }
-    l_platform_widget.push(Line::from("▜"));
+}
 
-    let l_pos = (app.game.l_pos * (frame_size.height - platform_height - 2) as f32).round() as u16;
-    let l_area = Rect::new(1, l_pos, 1, platform_height + 2);
-    frame.render_widget(Paragraph::new(l_platform_widget).white(), l_area);
+/// Draws the goal line of an eliminated side, which now bounces the ball back.
+fn render_wall(frame: &mut Frame, side: Side, palette: &Palette) {
+    let frame_size = frame.size();
+    let area = match side {
+        Side::Left => Rect::new(0, 0, 1, frame_size.height),
+        Side::Right => Rect::new(frame_size.width - 1, 0, 1, frame_size.height),
+        Side::Top => Rect::new(0, 0, frame_size.width, 1),
+        Side::Bottom => Rect::new(0, frame_size.height - 1, frame_size.width, 1),
+    };
+
+    frame.render_widget(Block::new().bg(palette.wall), area);
+}
 
-    let mut r_platform_widget: Vec<Line> = vec![Line::from("▙")];
-    if platform_height > 0 {
-        for _ in 0..platform_height {
-            r_platform_widget.push(Line::from(font::PLAT));
+/// Draws the obstacles of the arena and the walls either side of narrowed goals.
+fn render_arena(frame: &mut Frame, game: &Game, settings: &Settings) {
+    let (palette, glyphs) = (settings.palette(), settings.glyphs());
+    let (width, height) = game.field;
+    let arena = &game.arena;
+
+    for y in 0..height {
+        for x in 0..width {
+            let paragraph = match arena.tile_at((x as i16, y as i16), game.field, game.ticks) {
+                Tile::Empty => continue,
+                Tile::Block => Paragraph::new(glyphs.block).fg(palette.block),
+                Tile::Bumper => Paragraph::new(glyphs.bumper).fg(palette.bumper),
+                Tile::Portal(letter) => {
+                    Paragraph::new(letter.to_string()).fg(palette.portal).bold()
+                }
+            };
+            frame.render_widget(paragraph, Rect::new(x, y, 1, 1));
         }
     }
-    r_platform_widget.push(Line::from("▛"));
 
-    let r_pos = (app.game.r_pos * (frame_size.height - platform_height - 2) as f32).round() as u16;
-    let r_area = Rect::new(frame_size.width - 2, r_pos, 1, platform_height + 2);
-    frame.render_widget(Paragraph::new(r_platform_widget).white(), r_area);
+    for side in Side::ALL {
+        if game.is_wall(side) {
+            continue;
+        }
 
-    // Rendering the BALL.
-    let mut b_pos = [
-        (app.game.b_pos[0] * (frame_size.width - 1) as f32).round() as i16,
-        (app.game.b_pos[1] * (frame_size.height - 1) as f32).round() as i16,
-    ];
-    let b_area = Rect::new(b_pos[0] as u16, b_pos[1] as u16, 1, 1);
-    frame.render_widget(Paragraph::new(font::BALL).white(), b_area);
-
-    if !app.game.game_paused && !app.game.game_over && update_ball {
-        b_pos[0] += app.game.b_vel[0];
-        b_pos[1] += app.game.b_vel[1];
-        // Collision detection.
-        let in_range = |var: i16, min: i16, max: i16| var >= min && var <= max;
-
-        if app.game.b_vel[0] < 0
-            && b_pos[0] - 1 == 1
-            && in_range(b_pos[1] - l_pos as i16, 0, platform_height as i16)
-        {
-            app.game.b_vel[0] *= -1;
-        } else if app.game.b_vel[0] > 0
-            && b_pos[0] + 2 == (frame_size.width - 1) as i16
-            && in_range(b_pos[1] - r_pos as i16, 0, platform_height as i16)
-        {
-            app.game.b_vel[0] *= -1;
+        let length = if side.is_vertical() { height } else { width };
+        for i in 0..length {
+            if arena.is_goal(i as f32 / (length - 1) as f32) {
+                continue;
+            }
+            let area = match side {
+                Side::Left => Rect::new(0, i, 1, 1),
+                Side::Right => Rect::new(width - 1, i, 1, 1),
+                Side::Top => Rect::new(i, 0, 1, 1),
+                Side::Bottom => Rect::new(i, height - 1, 1, 1),
+            };
+            frame.render_widget(Block::new().bg(palette.wall), area);
         }
+    }
+}
 
-        if b_pos[0] == 0 {
-            app.game.r_score += 1;
-            app.game.reset();
-            return;
-        } else if b_pos[0] == (frame_size.width - 1) as i16 {
-            app.game.l_score += 1;
-            app.game.reset();
-            return;
+/// Draws the standing bricks of `GameMode::Breakout`, every brick in its own colour.
+fn render_bricks(frame: &mut Frame, game: &Game, settings: &Settings) {
+    let colors = &settings.palette().bricks;
+    let glyph = settings.glyphs().brick;
+    let (width, height) = game.field;
+
+    for y in 0..height {
+        for x in 0..width {
+            if let Some(brick) = game.brick_at((x as i16, y as i16)) {
+                let color = colors[(brick / BRICK_COLUMNS + brick) % colors.len()];
+                frame.render_widget(Paragraph::new(glyph).fg(color), Rect::new(x, y, 1, 1));
+            }
         }
+    }
+}
 
-        if b_pos[1] == 0 {
-            app.game.b_vel[1] *= -1;
-        } else if b_pos[1] == (frame_size.height - 1) as i16 {
-            app.game.b_vel[1] *= -1;
+/// Names every computer player in the bottom corner of its side.
+fn render_cpu_labels(frame: &mut Frame, game: &Game, palette: &Palette) {
+    let frame_size = frame.size();
+    let width = frame_size.width / 3;
+
+    for (side, alignment, x) in [
+        (Side::Left, Alignment::Left, 3),
+        (
+            Side::Right,
+            Alignment::Right,
+            frame_size.width.saturating_sub(3 + width),
+        ),
+    ] {
+        let controller = game.controllers[side.index()].name();
+        let name = match (&game.names[side.index()], controller) {
+            (Some(name), Some(controller)) => Some(format!("{name} ({controller})")),
+            (name, controller) => name.clone().or(controller),
+        };
+        if let Some(name) = name {
+            let area = Rect::new(x, frame_size.height.saturating_sub(2), width, 1);
+            frame.render_widget(
+                Paragraph::new(name).alignment(alignment).fg(palette.label),
+                area.intersection(frame_size),
+            );
         }
+    }
+}
 
-        app.game.b_pos = [
-            b_pos[0] as f32 / (frame_size.width - 1) as f32,
-            b_pos[1] as f32 / (frame_size.height - 1) as f32,
-        ];
-    } else if app.game.game_paused {
-        add_pause_menu(frame, app);
-    } else if app.game.game_over {
-        add_game_over_menu(frame, app);
+/// Makes `style` blink, unless blinking is turned off in the settings.
+fn blink(app: &App, style: Style) -> Style {
+    if app.settings.blinking {
+        style.slow_blink()
+    } else {
+        style
+    }
+}
+
+fn score_style(app: &App, side: Side) -> Style {
+    if app.effects.is_pulsing(side) {
+        Style::new().fg(app.settings.palette().accent).bold()
+    } else {
+        Style::new().white()
+    }
+}
+
+fn render_trail(frame: &mut Frame, app: &App) {
+    let frame_size = frame.size();
+    let palette = app.settings.palette();
+    let trail = app.settings.glyphs().trail;
+    let trail_length = app.settings.ball_trail as usize;
+
+    // Drawn oldest first so newer, brighter segments end up on top.
+    for (i, pos) in app.game.trail.iter().take(trail_length).enumerate().rev() {
+        let shade = i * trail.len() / trail_length;
+        let area = Rect::new(
+            (pos[0] * (frame_size.width - 1) as f32).round() as u16,
+            (pos[1] * (frame_size.height - 1) as f32).round() as u16,
+            1,
+            1,
+        );
+        frame.render_widget(Paragraph::new(trail[shade]).fg(palette.trail[shade]), area);
+    }
+}
+
+fn render_particles(frame: &mut Frame, effects: &Effects, settings: &Settings) {
+    let sparks = settings.glyphs().sparks;
+    let frame_size = frame.size();
+
+    for particle in effects.particles.iter() {
+        let area = Rect::new(
+            (particle.pos[0] * (frame_size.width - 1) as f32).round() as u16,
+            (particle.pos[1] * (frame_size.height - 1) as f32).round() as u16,
+            1,
+            1,
+        );
+        let glyph = if particle.age < PARTICLE_LIFETIME / 2 {
+            sparks[0]
+        } else {
+            sparks[1]
+        };
+        frame.render_widget(Paragraph::new(glyph).fg(settings.palette().accent), area);
+    }
+}
+
+fn render_edge_flash(frame: &mut Frame, effects: &Effects, palette: &Palette) {
+    let frame_size = frame.size();
+
+    if let Some((side, _)) = effects.flash {
+        let area = match side {
+            Side::Left => Rect::new(0, 0, 1, frame_size.height),
+            Side::Right => Rect::new(frame_size.width - 1, 0, 1, frame_size.height),
+            Side::Top => Rect::new(0, 0, frame_size.width, 1),
+            Side::Bottom => Rect::new(0, frame_size.height - 1, frame_size.width, 1),
+        };
+        frame.render_widget(Block::new().bg(palette.flash), area);
+    }
+}
+
+fn shake_frame(frame: &mut Frame, offset: (i16, i16)) {
+    if offset == (0, 0) {
+        return;
+    }
+
+    let frame_size = frame.size();
+    let buffer = frame.buffer_mut();
+    let original = buffer.clone();
+    for y in 0..frame_size.height {
+        for x in 0..frame_size.width {
+            let source = (x as i16 - offset.0, y as i16 - offset.1);
+            let cell = buffer.get_mut(x, y);
+            if source.0 < 0
+                || source.1 < 0
+                || source.0 >= frame_size.width as i16
+                || source.1 >= frame_size.height as i16
+            {
+                cell.reset();
+            } else {
+                *cell = original.get(source.0 as u16, source.1 as u16).clone();
+            }
+        }
+    }
+}
+
+fn add_serve_overlay(frame: &mut Frame, app: &mut App) {
+    let frame_size = frame.size();
+    let center_y = frame_size.height / 2;
+
+    if app.game.awaiting_serve {
+        let server = app.game.server;
+        let hint = match keymap::bindings(app, Screen::Playing)
+            .find(|binding| binding.action == keymap::Action::Serve(server))
+        {
+            Some(binding) => format!(
+                "{server} serves, press {}",
+                keymap::key_names(binding.keys, app.settings.glyphs())
+            ),
+            None => format!("{server} serves"),
+        };
+        let hint_width = hint.chars().count() as u16 + 2;
+        let hint_area = Rect::new(
+            frame_size.width.saturating_sub(hint_width) / 2,
+            center_y,
+            hint_width,
+            1,
+        )
+        .intersection(frame_size);
+
+        frame.render_widget(Clear, hint_area);
+        frame.render_widget(
+            Paragraph::new(hint)
+                .alignment(Alignment::Center)
+                .fg(app.settings.palette().accent)
+                .bold(),
+            hint_area,
+        );
+        return;
     }
+
+    let count = app
+        .game
+        .serve_timer
+        .as_secs_f32()
+        .ceil()
+        .min(9.0)
+        .to_string();
+    let font = app.settings.font_style.font(
+        frame_size.height,
+        frame_size.width,
+        &count,
+        app.settings.ascii,
+    );
+    let digit_area = Rect::new(
+        frame_size.width.saturating_sub(font.width) / 2,
+        center_y.saturating_sub(font.height / 2),
+        font.width,
+        font.height,
+    )
+    .intersection(frame_size);
+
+    frame.render_widget(Clear, digit_area);
+    frame.render_widget(
+        BigText::new(&count, font).style(Style::new().fg(app.settings.palette().accent)),
+        digit_area,
+    );
+}
+
+fn render_clock(frame: &mut Frame, app: &App, area: Rect, font: &'static font::Font) {
+    let time_left = app.game.time_left();
+    let secs = time_left.as_secs();
+    let text = format!("{:02}:{:02}", secs / 60 % 100, secs % 60);
+    let style = if secs <= 10 {
+        Style::new().fg(app.settings.palette().accent)
+    } else {
+        Style::new().white()
+    };
+
+    let clock_width = font.text_width(&text);
+    let clock_area = Rect::new(
+        area.x + area.width.saturating_sub(clock_width) / 2,
+        0,
+        clock_width,
+        font.height,
+    )
+    .intersection(area);
+    frame.render_widget(Clear, clock_area);
+    frame.render_widget(BigText::new(&text, font).style(style), clock_area);
+}
+
+/// Draws `text` in big letters above a popup whose top row is `popup_y`, in a one row font when
+/// the bigger ones would run into the scores.
+fn render_banner(frame: &mut Frame, app: &App, text: &str, popup_y: u16) {
+    let frame_size = frame.size();
+    let mut font = app.settings.font_style.font(
+        frame_size.height,
+        frame_size.width,
+        text,
+        app.settings.ascii,
+    );
+    if font.height * 2 + 2 > popup_y {
+        font = if app.settings.ascii {
+            &font::PLAIN
+        } else {
+            &font::BRAILLE
+        };
+    }
+
+    let banner = BigText::new(text, font).style(Style::new().fg(app.settings.palette().accent));
+    let banner_area = Rect::new(
+        frame_size.width.saturating_sub(banner.width()) / 2,
+        popup_y.saturating_sub(font.height + 1),
+        banner.width(),
+        font.height,
+    )
+    .intersection(frame_size);
+    frame.render_widget(Clear, banner_area);
+    frame.render_widget(banner, banner_area);
 }
 
 fn add_pause_menu(frame: &mut Frame, app: &mut App) {
     let frame_size = frame.size();
-    const BUTTONS: [&str; PAUSE_MENU_BUTTON_COUNT] = ["Continue", "Help", "Restart", "Main Menu"];
+    const BUTTONS: [&str; PAUSE_MENU_BUTTON_COUNT] = PAUSE_MENU_BUTTONS;
 
     let layout_vert = Layout::new(
         Direction::Vertical,
@@ -384,79 +956,66 @@ fn add_pause_menu(frame: &mut Frame, app: &mut App) {
     )
     .split(frame_size);
 
-    let min_popup_width = BUTTONS.concat().len() as u16 + BUTTONS.len() as u16 * 8;
-    let popup_width = min_popup_width.max((frame_size.width as f32 / 2.0).round() as u16);
+    let popup_width = buttons_width(&BUTTONS)
+        .max((frame_size.width as f32 / 2.0).round() as u16)
+        .min(frame_size.width);
+    let button_rows = button_rows(&BUTTONS, popup_width);
+    // Room for the message, the buttons and the borders.
+    let popup_height = layout_vert[1]
+        .height
+        .max(button_rows + 3)
+        .min(frame_size.height);
     let popup_window = Rect::new(
-        (frame_size.width as f32 / 2.0 - popup_width as f32 / 2.0).round() as u16,
-        layout_vert[1].y,
+        frame_size.width.saturating_sub(popup_width) / 2,
+        frame_size.height.saturating_sub(popup_height) / 2,
         popup_width,
-        layout_vert[1].height,
-    );
+        popup_height,
+    )
+    .intersection(frame_size);
 
     let ui_block = Block::new()
-        .on_dark_gray()
+        .bg(app.settings.palette().popup)
         .borders(Borders::ALL)
-        .border_type(BorderType::Rounded)
+        .border_set(app.settings.glyphs().border)
         .border_style(Style::new().white())
         .padding(Padding::horizontal(2));
 
     frame.render_widget(Clear, popup_window);
 
+    render_banner(frame, app, "PAUSE", popup_window.y);
+
     let ui_layout = Layout::new(
         Direction::Vertical,
-        [Constraint::Min(1), Constraint::Max(1)],
+        [Constraint::Min(1), Constraint::Max(button_rows)],
     )
     .split(ui_block.inner(popup_window));
 
     frame.render_widget(ui_block, popup_window);
 
     frame.render_widget(
-        Paragraph::new("Game Paused!")
+        Paragraph::new(app.notice.as_deref().unwrap_or("Game Paused!"))
             .alignment(Alignment::Center)
-            .yellow()
+            .wrap(Wrap { trim: true })
+            .fg(app.settings.palette().accent)
             .bold(),
         ui_layout[0],
     );
 
-    let button_widget = |name: &str, i: i8| {
-        let mut button = if app.game.pause_menu_selected_button == i {
-            "> ".to_string()
-        } else {
-            "  ".to_string()
-        };
-        button.push_str(name);
-        button.push_str(if app.game.pause_menu_selected_button == i {
-            " <"
-        } else {
-            "  "
-        });
-
-        button
-    };
-
-    let button_layout = Layout::new(
-        Direction::Horizontal,
-        (0..BUTTONS.len()).map(|_| Constraint::Ratio(1, BUTTONS.len() as u32)),
-    )
-    .split(ui_layout[1]);
-
-    for i in 0..BUTTONS.len() {
-        frame.render_widget(
-            Paragraph::new(button_widget(BUTTONS[i], i as i8).as_str())
-                .alignment(Alignment::Center)
-                .white(),
-            button_layout[i],
-        );
-    }
+    render_buttons(
+        frame,
+        ui_layout[1],
+        &BUTTONS,
+        app.game.pause_menu_selected_button,
+    );
 
     if app.game.help_popup_shown {
-        show_help_popup(frame);
+        show_help_popup(frame, app);
     }
 }
 
 fn add_game_over_menu(frame: &mut Frame, app: &mut App) {
     let frame_size = frame.size();
-    const BUTTONS: [&str; GAME_OVER_MENU_BUTTON_COUNT] = ["Restart", "Main Menu"];
+    const BUTTONS: [&str; GAME_OVER_MENU_BUTTON_COUNT] = GAME_OVER_MENU_BUTTONS;
 
     let layout_vert = Layout::new(
         Direction::Vertical,
@@ -468,65 +1027,170 @@ fn add_game_over_menu(frame: &mut Frame, app: &mut App) {
     )
     .split(frame_size);
 
-    let min_popup_width = BUTTONS.concat().len() as u16 + BUTTONS.len() as u16 * 8;
-    let popup_width = min_popup_width.max((frame_size.width as f32 / 3.0).round() as u16);
+    let mut game_over_text: Vec<Line<'_>> = vec![Line::from("Game Over!"), Line::default()];
+    if app.game.mode.is_solo() {
+        let elapsed = app.game.elapsed.as_secs();
+        game_over_text.push(Line::styled(
+            format!("Score {}", app.game.score(Side::Left)),
+            blink(app, Style::new().fg(app.settings.palette().winner).bold()),
+        ));
+        game_over_text.push(Line::from(format!(
+            "Survived {}:{:02}",
+            elapsed / 60,
+            elapsed % 60
+        )));
+        game_over_text.push(Line::from(format!("Best streak {}", app.game.best_streak)));
+    } else {
+        let winner = match app.game.winner() {
+            Some(side) => app.game.player_name(side),
+            None => "WTF".to_string(),
+        };
+        game_over_text.push(Line::styled(
+            format!("{winner} Wins!"),
+            blink(app, Style::new().fg(app.settings.palette().winner).bold()),
+        ));
+    }
+
+    let popup_width = buttons_width(&BUTTONS)
+        .max((frame_size.width as f32 / 3.0).round() as u16)
+        .min(frame_size.width);
+    let button_rows = button_rows(&BUTTONS, popup_width);
+    // Room for the text, the buttons and the borders.
+    let popup_height = layout_vert[1]
+        .height
+        .max(game_over_text.len() as u16 + button_rows + 2)
+        .min(frame_size.height);
     let popup_window = Rect::new(
-        (frame_size.width as f32 / 2.0 - popup_width as f32 / 2.0).round() as u16,
-        layout_vert[1].y,
+        frame_size.width.saturating_sub(popup_width) / 2,
+        frame_size.height.saturating_sub(popup_height) / 2,
         popup_width,
-        layout_vert[1].height,
-    );
+        popup_height,
+    )
+    .intersection(frame_size);
 
     let ui_block = Block::new()
-        .on_dark_gray()
+        .bg(app.settings.palette().popup)
         .borders(Borders::ALL)
-        .border_type(BorderType::Rounded)
+        .border_set(app.settings.glyphs().border)
         .border_style(Style::new().white())
         .padding(Padding::horizontal(2));
 
     frame.render_widget(Clear, popup_window);
 
+    let banner = match app.game.winner() {
+        Some(side) if !app.game.mode.is_solo() => {
+            format!("{} WINS", side.to_string().to_uppercase())
+        }
+        _ => "GAME OVER".to_string(),
+    };
+    render_banner(frame, app, &banner, popup_window.y);
+
     let ui_layout = Layout::new(
         Direction::Vertical,
-        [Constraint::Min(1), Constraint::Max(1)],
+        [Constraint::Min(1), Constraint::Max(button_rows)],
     )
     .split(ui_block.inner(popup_window));
 
     frame.render_widget(ui_block, popup_window);
 
-    let winner: &str = if app.game.l_score > app.game.r_score {
-        "Left"
-    } else if app.game.r_score > app.game.l_score {
-        "Right"
-    } else {
-        "WTF"
-    };
-    let mut game_over_text: Vec<Line<'_>> = vec![Line::from("Game Over!"), Line::default()];
-    game_over_text.push(Line::styled(
-        format!("{winner} Wins!"),
-        Style::new().green().bold().slow_blink(),
-    ));
-
     frame.render_widget(
         Paragraph::new(game_over_text).alignment(Alignment::Center),
         ui_layout[0],
     );
 
-    let create_button = |name: &str, i: i8| {
-        let mut button = if app.game.game_over_menu_selected_button == i {
-            "> ".to_string()
+    render_buttons(
+        frame,
+        ui_layout[1],
+        &BUTTONS,
+        app.game.game_over_menu_selected_button,
+    );
+}
+
+/// Width the popup buttons take side by side, with room around each.
+fn buttons_width(buttons: &[&str]) -> u16 {
+    buttons.concat().len() as u16 + buttons.len() as u16 * 8
+}
+
+/// Rows the buttons take in a popup `width` wide, one for all of them or one each when they
+/// don't fit side by side.
+fn button_rows(buttons: &[&str], width: u16) -> u16 {
+    if buttons_width(buttons) <= width {
+        1
+    } else {
+        buttons.len() as u16
+    }
+}
+
+/// Draws the buttons of a popup menu, the `selected` one between arrows.
+fn render_buttons(frame: &mut Frame, area: Rect, buttons: &[&str], selected: i8) {
+    // Laid out by `button_rows`, which gives them a row each when they don't fit in one.
+    let layout = if area.height > 1 {
+        Layout::new(
+            Direction::Vertical,
+            buttons.iter().map(|_| Constraint::Length(1)),
+        )
+    } else {
+        Layout::new(
+            Direction::Horizontal,
+            buttons
+                .iter()
+                .map(|_| Constraint::Ratio(1, buttons.len() as u32)),
+        )
+    }
+    .split(area);
+
+    for (i, name) in buttons.iter().enumerate() {
+        let button = if selected == i as i8 {
+            format!("> {name} <")
         } else {
-            "  ".to_string()
+            format!("  {name}  ")
         };
-        button.push_str(name);
-        button.push_str(if app.game.game_over_menu_selected_button == i {
-            " <"
-        } else {
-            "  "
-        });
+        frame.render_widget(
+            Paragraph::new(button).alignment(Alignment::Center).white(),
+            layout[i],
+        );
+    }
+}
 
-        button
-    };
+fn render_confirm_popup(frame: &mut Frame, app: &App, confirm: Confirm) {
+    let frame_size = frame.size();
+    const BUTTONS: [&str; CONFIRM_MENU_BUTTON_COUNT] = CONFIRM_MENU_BUTTONS;
+
+    let text: Vec<Line<'_>> = vec![
+        Line::styled(
+            confirm.question(),
+            Style::new().fg(app.settings.palette().accent).bold(),
+        ),
+        Line::from("The match in progress will be lost."),
+    ];
+    let popup_width = (text[1].width() as u16 + 8).min(frame_size.width);
+    let popup_window = Rect::new(
+        frame_size.width.saturating_sub(popup_width) / 2,
+        frame_size.height.saturating_sub(6) / 2,
+        popup_width,
+        6,
+    )
+    .intersection(frame_size);
+
+    let ui_block = Block::new()
+        .bg(app.settings.palette().popup)
+        .borders(Borders::ALL)
+        .border_set(app.settings.glyphs().border)
+        .border_style(Style::new().white())
+        .padding(Padding::horizontal(2));
+
+    let ui_layout = Layout::new(
+        Direction::Vertical,
+        [Constraint::Min(1), Constraint::Max(1)],
+    )
+    .split(ui_block.inner(popup_window));
+
+    frame.render_widget(Clear, popup_window);
+    frame.render_widget(ui_block, popup_window);
+    frame.render_widget(
+        Paragraph::new(text).alignment(Alignment::Center).white(),
+        ui_layout[0],
+    );
 
     let button_layout = Layout::new(
         Direction::Horizontal,
@@ -534,28 +1198,28 @@ fn add_game_over_menu(frame: &mut Frame, app: &mut App) {
     )
     .split(ui_layout[1]);
 
-    let mut buttons_text: Vec<String> = vec![];
-    for i in 0..BUTTONS.len() {
-        buttons_text.push(create_button(BUTTONS[i], i as i8));
-
+    for (i, name) in BUTTONS.iter().enumerate() {
+        let button = if app.confirm_selected_button == i as i8 {
+            format!("> {name} <")
+        } else {
+            name.to_string()
+        };
         frame.render_widget(
-            Paragraph::new(buttons_text[i].as_str())
-                .alignment(Alignment::Center)
-                .white(),
+            Paragraph::new(button).alignment(Alignment::Center).white(),
             button_layout[i],
         );
     }
 }
 
-fn show_help_popup(frame: &mut Frame) {
+fn show_help_popup(frame: &mut Frame, app: &mut App) {
     let frame_size = frame.size();
 
     let layout_vert = Layout::new(
         Direction::Vertical,
         [
-            Constraint::Ratio(1, 4),
-            Constraint::Ratio(1, 2),
-            Constraint::Ratio(1, 4),
+            Constraint::Ratio(1, 8),
+            Constraint::Ratio(3, 4),
+            Constraint::Ratio(1, 8),
         ],
     )
     .split(frame_size);
@@ -566,40 +1230,147 @@ fn show_help_popup(frame: &mut Frame) {
         layout_vert[1].y,
         popup_width,
         layout_vert[1].height,
-    );
+    )
+    .intersection(frame_size);
 
-    let text_block = Block::new()
-        .on_dark_gray()
+    let mut text_block = Block::new()
+        .bg(app.settings.palette().popup)
         .borders(Borders::ALL)
-        .border_type(BorderType::Rounded)
+        .border_set(app.settings.glyphs().border)
         .border_style(Style::new().white())
         .padding(Padding::horizontal(1))
         .title("Press Esc to close this window.")
         .title_alignment(Alignment::Center)
         .title_position(Position::Bottom)
-        .title_style(Style::new().light_yellow());
+        .title_style(Style::new().fg(app.settings.palette().accent));
+    let text_area = text_block.inner(popup_window);
+    let width = text_area.width as usize;
 
-    frame.render_widget(Clear, popup_window);
+    let heading = |text: String| {
+        [
+            Line::default(),
+            Line::styled(text, Style::new().bold().fg(app.settings.palette().accent)),
+        ]
+    };
+    let mut help_text: Vec<Line<'_>> = vec![Line::styled(
+        "Help",
+        Style::new().bold().fg(app.settings.palette().accent),
+    )
+    .alignment(Alignment::Center)];
 
-    let mut help_text: Vec<Line<'_>> = vec![
-        Line::styled("Help", Style::new().bold().yellow()).alignment(Alignment::Center),
-        Line::default(),
-    ];
+    // The keys of the screen under the help, and the match keys for people yet to play one.
+    let screens: &[Screen] = if app.game_started {
+        &[Screen::Playing]
+    } else {
+        &[Screen::MainMenu, Screen::Playing]
+    };
+    for screen in screens {
+        help_text.extend(heading(screen.to_string()));
+        for binding in keymap::bindings(app, *screen) {
+            help_text.extend(help_entry(
+                &keymap::key_names(binding.keys, app.settings.glyphs()),
+                binding.description,
+                width,
+            ));
+        }
+    }
 
-    help_text.extend([
-        Line::from("Up/Down, W/S: moves the Left/Right platforms up and down."),
-        Line::default(),
-    ]);
-    help_text.extend([Line::from("P: pause game."), Line::default()]);
-    help_text.extend([Line::from("H: show this window while in the game."), Line::default()]);
-    help_text.extend([Line::from("Esc: force quit the app."), Line::default()]);
+    help_text.extend(heading("Modes".to_string()));
+    for mode in (0..GameMode::len() as i8).filter_map(|i| GameMode::try_from(i).ok()) {
+        help_text.extend(help_entry(
+            mode.to_string().trim(),
+            mode.description(),
+            width,
+        ));
+    }
 
+    help_text.extend(heading("Scoring".to_string()));
+    for rule in (0..ScoringRule::len() as i8).filter_map(|i| ScoringRule::try_from(i).ok()) {
+        help_text.extend(help_entry(
+            rule.to_string().trim(),
+            rule.description(),
+            width,
+        ));
+    }
+
+    let max_scroll = (help_text.len() as u16).saturating_sub(text_area.height);
+    app.help_scroll = app.help_scroll.min(max_scroll);
+    if max_scroll > 0 {
+        let glyphs = app.settings.glyphs();
+        let arrows = match app.help_scroll {
+            0 => glyphs.down.to_string(),
+            scroll if scroll == max_scroll => glyphs.up.to_string(),
+            _ => format!("{}{}", glyphs.up, glyphs.down),
+        };
+        text_block = text_block.title(
+            Title::from(format!(" {arrows} "))
+                .alignment(Alignment::Right)
+                .position(Position::Top),
+        );
+    }
+
+    frame.render_widget(Clear, popup_window);
+    frame.render_widget(text_block, popup_window);
     frame.render_widget(
         Paragraph::new(help_text)
-            .wrap(Wrap { trim: true })
             .white()
             .alignment(Alignment::Left)
-            .block(text_block.clone()),
-        text_block.inner(popup_window),
+            .scroll((app.help_scroll, 0)),
+        text_area,
+    );
+}
+
+/// Lays out one line of the help, the description wrapped to `width` and lined up after `name`.
+fn help_entry(name: &str, description: &str, width: usize) -> Vec<Line<'static>> {
+    const NAME_WIDTH: usize = 12;
+    let text_width = width.saturating_sub(NAME_WIDTH).max(10);
+
+    let mut rows: Vec<String> = vec![];
+    for word in description.split_whitespace() {
+        match rows.last_mut() {
+            Some(row) if row.chars().count() + 1 + word.chars().count() <= text_width => {
+                row.push(' ');
+                row.push_str(word);
+            }
+            _ => rows.push(word.to_string()),
+        }
+    }
+
+    rows.into_iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let name = if i == 0 { name } else { "" };
+            Line::from(vec![
+                Span::styled(format!("{name: <NAME_WIDTH$}"), Style::new().light_cyan()),
+                Span::raw(row),
+            ])
+        })
+        .collect()
+}
+
+/// Draws the keys of the current screen on the last row. Left out during play, where the row
+/// belongs to the field.
+fn render_footer(frame: &mut Frame, app: &App) {
+    let screen = Screen::current(app);
+    if screen == Screen::Playing {
+        return;
+    }
+    let frame_size = frame.size();
+    let footer = keymap::footer(app, screen);
+    // Stays clear of the paddles on the left and right edges.
+    let area = Rect::new(
+        2,
+        frame_size.height.saturating_sub(1),
+        frame_size.width.saturating_sub(4),
+        1,
+    )
+    .intersection(frame_size);
+    let width = (footer.chars().count() as u16).min(area.width);
+    let area = Rect::new(area.x + (area.width - width) / 2, area.y, width, 1);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(footer).fg(app.settings.palette().label),
+        area,
     );
 }
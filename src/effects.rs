@@ -0,0 +1,137 @@
+use crate::game::{GameEvent, Side};
+use rand::Rng;
+use std::time::Duration;
+
+pub const FLASH_DURATION: Duration = Duration::from_millis(300);
+pub const PULSE_DURATION: Duration = Duration::from_millis(900);
+pub const PULSE_PERIOD: Duration = Duration::from_millis(150);
+pub const SHAKE_DURATION: Duration = Duration::from_millis(250);
+pub const PARTICLE_LIFETIME: Duration = Duration::from_millis(400);
+pub const PARTICLE_COUNT: usize = 6;
+
+pub struct Particle {
+    pub pos: [f32; 2],
+    /// Field fractions per second.
+    pub vel: [f32; 2],
+    pub age: Duration,
+}
+
+/// Short lived animations triggered by the `GameEvent`s of a match.
+///
+/// Everything is aged by frame time, so effects last as long on a slow terminal as on a fast one.
+#[derive(Default)]
+pub struct Effects {
+    pub flash: Option<(Side, Duration)>,
+    pub pulse: Option<(Side, Duration)>,
+    pub shake: Option<Duration>,
+    pub particles: Vec<Particle>,
+}
+
+impl Effects {
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn handle(&mut self, event: GameEvent) {
+        match event {
+            GameEvent::Goal { scorer, conceder } => {
+                // Nobody gets the credit for an own goal, so the goal line flashes instead.
+                self.flash = Some((scorer.unwrap_or(conceder), Duration::ZERO));
+                if let Some(side) = scorer {
+                    self.pulse = Some((side, Duration::ZERO));
+                }
+                self.shake = Some(Duration::ZERO);
+            }
+            GameEvent::PaddleHit(side, pos) => {
+                let direction = match side {
+                    Side::Left | Side::Top => 1.0,
+                    Side::Right | Side::Bottom => -1.0,
+                };
+                let mut rng = rand::thread_rng();
+                for _ in 0..PARTICLE_COUNT {
+                    // Particles spray away from the paddle and fan out along it.
+                    let away = direction * rng.gen_range(0.05..0.2);
+                    let along = rng.gen_range(-0.6..0.6);
+                    self.particles.push(Particle {
+                        pos,
+                        vel: if side.is_vertical() {
+                            [away, along]
+                        } else {
+                            [along, away * 2.0]
+                        },
+                        age: Duration::ZERO,
+                    });
+                }
+            }
+            GameEvent::BrickBroken(pos) => {
+                let mut rng = rand::thread_rng();
+                for _ in 0..PARTICLE_COUNT {
+                    self.particles.push(Particle {
+                        pos,
+                        vel: [rng.gen_range(-0.2..0.2), rng.gen_range(-0.4..0.4)],
+                        age: Duration::ZERO,
+                    });
+                }
+            }
+            GameEvent::WallBounce => (),
+        }
+    }
+
+    pub fn update(&mut self, delta: Duration) {
+        let age = |effect: &mut Option<(Side, Duration)>, lifetime: Duration| {
+            if let Some((_, elapsed)) = effect {
+                *elapsed += delta;
+                if *elapsed >= lifetime {
+                    *effect = None;
+                }
+            }
+        };
+        age(&mut self.flash, FLASH_DURATION);
+        age(&mut self.pulse, PULSE_DURATION);
+
+        if let Some(elapsed) = &mut self.shake {
+            *elapsed += delta;
+            if *elapsed >= SHAKE_DURATION {
+                self.shake = None;
+            }
+        }
+
+        for particle in self.particles.iter_mut() {
+            particle.age += delta;
+            particle.pos[0] += particle.vel[0] * delta.as_secs_f32();
+            particle.pos[1] += particle.vel[1] * delta.as_secs_f32();
+        }
+        self.particles.retain(|particle| {
+            particle.age < PARTICLE_LIFETIME
+                && (0.0..=1.0).contains(&particle.pos[0])
+                && (0.0..=1.0).contains(&particle.pos[1])
+        });
+    }
+
+    /// Whether the score of `side` is in the bright half of a pulse.
+    pub fn is_pulsing(&self, side: Side) -> bool {
+        match self.pulse {
+            Some((pulse_side, elapsed)) => {
+                pulse_side == side
+                    && (elapsed.as_millis() / PULSE_PERIOD.as_millis()).is_multiple_of(2)
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the offset of the whole field while the screen is shaking.
+    pub fn shake_offset(&self) -> (i16, i16) {
+        match self.shake {
+            Some(elapsed) => {
+                let step = elapsed.as_millis() / 50;
+                match step % 4 {
+                    0 => (1, 0),
+                    1 => (-1, 1),
+                    2 => (1, -1),
+                    _ => (-1, 0),
+                }
+            }
+            None => (0, 0),
+        }
+    }
+}
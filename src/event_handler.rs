@@ -1,151 +1,164 @@
-use crate::app::App;
-use crossterm::event::{Event, KeyCode, KeyEventKind};
+use crate::app::*;
+use crate::keymap::{self, Action, Screen};
+use crate::menu;
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
 use std::io::Result;
+use std::time::Duration;
+
+/// Lines PgUp and PgDn scroll the help by.
+const HELP_PAGE: u16 = 10;
 
 pub fn handle(event: Event, app: &mut App) -> Result<()> {
+    // Pastes arrive whole when the terminal supports bracketed paste, and only go into text.
+    if let Event::Paste(text) = &event {
+        if Screen::current(app) == Screen::TextInput {
+            if let Some(menu) = menu::shown(app) {
+                menu.paste(app, text);
+            }
+        }
+        return Ok(());
+    }
+
     if let Event::Key(key) = event {
         if key.kind != KeyEventKind::Press {
             return Ok(());
         }
 
-        match key.code {
-            KeyCode::Esc => {
-                if app.game_started && app.game.game_paused {
-                    app.game.resume_game();
-                } else if app.help_popup_shown {
-                    app.hide_help_popup();
-                } else {
-                    app.should_quit = true;
-                }
-            }
-            KeyCode::Char('p') => {
-                if app.game_started && !app.game.game_over {
-                    if app.game.game_paused {
-                        app.game.resume_game();
-                    } else {
-                        app.game.pause_game();
-                    }
-                }
-            }
-            KeyCode::Char('w') => {
-                if app.game_started && !app.game.game_paused && !app.game.game_over {
-                    if app.game.l_pos <= 0.0 {
-                        ()
-                    }
-                    let platform_height = 3 - app.difficulty as u16;
-                    let view_height = (app.frame_size.height - platform_height - 2) as f32;
+        app.idle = Duration::ZERO;
+        app.notice = None;
+        if app.attract {
+            app.stop_attract();
+        }
 
-                    app.game.l_pos =
-                        0.0f32.max((f32::round(app.game.l_pos * view_height) - 1.0) / view_height);
-                }
+        let screen = Screen::current(app);
+        let Some(action) = keymap::action(key, screen) else {
+            let typed = match key.code {
+                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => Some(c),
+                _ => None,
+            };
+            if let (Screen::TextInput, Some(c), Some(menu)) = (screen, typed, menu::shown(app)) {
+                menu.type_char(app, c);
             }
-            KeyCode::Char('s') => {
-                if app.game_started && !app.game.game_paused && !app.game.game_over {
-                    if app.game.l_pos >= 1.0 {
-                        ()
-                    }
-                    let platform_height = 3 - app.game.difficulty as u16;
-                    let view_height = (app.frame_size.height - platform_height - 2) as f32;
+            return Ok(());
+        };
 
-                    app.game.l_pos =
-                        1.0f32.min((f32::round(app.game.l_pos * view_height) + 1.0) / view_height);
+        match action {
+            Action::Quit => app.ask(Confirm::Quit),
+            Action::ForceQuit => app.should_quit = true,
+            Action::Back if screen == Screen::CustomDifficulty => app.hide_custom_menu(),
+            Action::Back => app.show_main_menu(),
+            Action::Accept | Action::Cancel | Action::Edit(_) if screen == Screen::TextInput => {
+                if let Some(menu) = menu::shown(app) {
+                    match action {
+                        Action::Accept => menu.finish_editing(app),
+                        Action::Cancel => menu.cancel_editing(app),
+                        Action::Edit(edit) => menu.edit(app, edit),
+                        _ => (),
+                    }
                 }
             }
-            KeyCode::Char('h') => {
-                if app.game_started && !app.game.game_paused && !app.game.game_over {
-                    app.game.show_help_popup();
+            Action::Accept => app.accept_confirm(),
+            Action::Cancel => app.cancel_confirm(),
+            Action::Edit(_) => (),
+            Action::CloseHelp => {
+                if app.game_started {
+                    app.game.help_popup_shown = false;
+                } else {
+                    app.hide_help_popup();
                 }
             }
-            KeyCode::Up => {
-                if app.main_menu_shown && !app.help_popup_shown {
-                    app.dec_main_menu_button();
-                } else if app.difficulty_menu_shown {
-                    app.dec_difficulty_menu_button();
-                } else if app.game_started && !app.game.game_paused && !app.game.game_over {
-                    if app.game.r_pos <= 0.0 {
-                        ()
+            Action::ScrollUp => app.help_scroll = app.help_scroll.saturating_sub(1),
+            Action::ScrollDown => app.help_scroll = app.help_scroll.saturating_add(1),
+            Action::PageUp => app.help_scroll = app.help_scroll.saturating_sub(HELP_PAGE),
+            Action::PageDown => app.help_scroll = app.help_scroll.saturating_add(HELP_PAGE),
+            Action::MenuPrev => match screen {
+                Screen::MainMenu => app.dec_main_menu_button(),
+                Screen::Paused => app.game.dec_pause_menu_button(),
+                Screen::GameOver => app.game.dec_game_over_menu_button(),
+                Screen::Confirm => app.dec_confirm_button(),
+                _ => {
+                    if let Some(menu) = menu::shown(app) {
+                        menu.select_prev(app);
                     }
-                    let platform_height = 3 - app.game.difficulty as u16;
-                    let view_height = (app.frame_size.height - platform_height - 2) as f32;
-
-                    app.game.r_pos =
-                        0.0f32.max((f32::round(app.game.r_pos * view_height) - 1.0) / view_height);
                 }
-            }
-            KeyCode::Down => {
-                if app.main_menu_shown && !app.help_popup_shown {
-                    app.inc_main_menu_button();
-                } else if app.difficulty_menu_shown {
-                    app.inc_difficulty_menu_button();
-                } else if app.game_started && !app.game.game_paused && !app.game.game_over {
-                    if app.game.r_pos >= 1.0 {
-                        ()
+            },
+            Action::MenuNext => match screen {
+                Screen::MainMenu => app.inc_main_menu_button(),
+                Screen::Paused => app.game.inc_pause_menu_button(),
+                Screen::GameOver => app.game.inc_game_over_menu_button(),
+                Screen::Confirm => app.inc_confirm_button(),
+                _ => {
+                    if let Some(menu) = menu::shown(app) {
+                        menu.select_next(app);
                     }
-                    let platform_height = 3 - app.game.difficulty as u16;
-                    let view_height = (app.frame_size.height - platform_height - 2) as f32;
-
-                    app.game.r_pos =
-                        1.0f32.min((f32::round(app.game.r_pos * view_height) + 1.0) / view_height);
                 }
-            }
-            KeyCode::Right => {
-                if app.difficulty_menu_shown {
-                    match app.difficulty_menu_selected_button {
-                        0 => app.inc_difficulty(),
-                        1 => app.inc_win_score(),
-                        _ => (),
-                    }
-                } else if app.game_started && app.game.game_paused {
-                    app.game.inc_pause_menu_button();
-                } else if app.game_started && app.game.game_over {
-                    app.game.inc_game_over_menu_button();
+            },
+            Action::ChoicePrev => {
+                if let Some(menu) = menu::shown(app) {
+                    menu.change(app, -1);
                 }
             }
-            KeyCode::Left => {
-                if app.difficulty_menu_shown {
-                    match app.difficulty_menu_selected_button {
-                        0 => app.dec_difficulty(),
-                        1 => app.dec_win_score(),
-                        _ => (),
-                    }
-                } else if app.game_started && app.game.game_paused {
-                    app.game.dec_pause_menu_button();
-                } else if app.game_started && app.game.game_over {
-                    app.game.dec_game_over_menu_button();
+            Action::ChoiceNext => {
+                if let Some(menu) = menu::shown(app) {
+                    menu.change(app, 1);
                 }
             }
-            KeyCode::Enter => {
-                if app.main_menu_shown {
-                    match app.main_menu_selected_button {
-                        0 => app.show_difficulty_select_menu(),
-                        1 => app.show_help_popup(),
-                        2 => app.should_quit = true,
-                        _ => (),
-                    }
-                } else if app.difficulty_menu_shown {
-                    match app.difficulty_menu_selected_button {
-                        2 => app.start_game(),
-                        3 => app.show_main_menu(),
-                        _ => (),
-                    }
-                } else if app.game_started && app.game.game_paused {
-                    match app.game.pause_menu_selected_button {
-                        0 => app.game.resume_game(),
-                        1 => app.game.show_help_popup(),
-                        2 => app.show_difficulty_select_menu(),
-                        3 => app.show_main_menu(),
-                        _ => (),
-                    }
-                } else if app.game_started && app.game.game_over {
-                    match app.game.game_over_menu_selected_button {
-                        0 => app.show_difficulty_select_menu(),
-                        1 => app.show_main_menu(),
-                        _ => (),
-                    }
-                }
+            Action::Select => select(app, screen),
+            Action::MoveUp(side) => app.game.move_paddle(side, -1),
+            Action::MoveDown(side) => app.game.move_paddle(side, 1),
+            Action::Serve(side) => app.game.serve(side),
+            Action::Pause => app.game.pause_game(),
+            Action::Resume => app.game.resume_game(),
+            Action::Help => {
+                app.help_scroll = 0;
+                app.game.show_help_popup();
             }
-            _ => (),
+            Action::Mute => app.toggle_mute(),
         }
     }
     Ok(())
 }
+
+/// Presses the selected button or item of the menu on `screen`.
+fn select(app: &mut App, screen: Screen) {
+    match screen {
+        Screen::MainMenu => match app.main_menu_selected_button {
+            0 => app.continue_game(),
+            1 => app.show_difficulty_select_menu(),
+            2 => app.show_watch_menu(),
+            3 => app.show_settings_menu(),
+            4 => app.show_help_popup(),
+            5 => app.ask(Confirm::Quit),
+            _ => (),
+        },
+        Screen::Settings | Screen::Setup | Screen::CustomDifficulty => {
+            if let Some(menu) = menu::shown(app) {
+                menu.press(app);
+            }
+        }
+        Screen::Paused => match app.game.pause_menu_selected_button {
+            0 => app.game.resume_game(),
+            1 => {
+                app.help_scroll = 0;
+                app.game.show_help_popup();
+            }
+            2 => app.ask(Confirm::Restart),
+            3 => app.save_and_quit(),
+            4 => app.ask(Confirm::MainMenu),
+            _ => (),
+        },
+        Screen::Confirm => {
+            if app.confirm_selected_button == 0 {
+                app.accept_confirm();
+            } else {
+                app.cancel_confirm();
+            }
+        }
+        Screen::GameOver => match app.game.game_over_menu_selected_button {
+            0 => app.show_difficulty_select_menu(),
+            1 => app.show_main_menu(),
+            _ => (),
+        },
+        _ => (),
+    }
+}
@@ -1,14 +1,10 @@
+use ratatui::{buffer::Buffer, layout::*, style::Style, symbols::border, widgets::Widget};
+use std::fmt::Display;
+
 //▁ ▂ ▃ ▄ ▅ ▆ ▇ █ ▉ ▊ ▋ ▌ ▍ ▎ ▏ ▐ ▓ ▔ ▕ ▖ ▗ ▘ ▙ ▚ ▛ ▜ ▝ ▞ ▟ ■ ▀
 
-pub const PONG_TUI: &str = "╔═════════════════════════════════════════════════════════════════════╗
-║ █▀▀▀▀▀▙                                    ▜█████▛  ▟     ▙  ▝█████▘║
-║ █     █  ▗▆▆▆▆▆▖  █▙    █  ▟▀▀▀▀▀▙            █     █     █     █   ║
-║ █     █  █     █  █▜▙   █  █                  █     █     █     █   ║
-║▄█▆▆▆▆▆▛  █     █  █ ▜▙  █  █        ▅▅▅▅▅     █     █     █     █   ║
-║ █        █     █  █  ▜▙ █  █  ▀▀▀▙  ▀▀▀▀▀     █     █     █     █   ║
-║ █        █     █  █   ▜▙█  █     █            █     █     █     █   ║
-║ █        ▝▆▆▆▆▆▘  █    ▜█  ▜▆▆▆▆▆▛            █     ▜▆▆▆▆▆▛  ▗▆▆█▆▆▖║
-╚═════════════════════════════════════════════════════════════════════╝";
+pub const NUM_WIDTH: u16 = 6;
+pub const NUM_HEIGHT: u16 = 5;
 
 pub const NUMS: [&str; 10] = [
     r#" ▗▆▆▆▖
@@ -63,6 +59,732 @@ pub const NUMS: [&str; 10] = [
  ▗▇▇▇▘"#,
 ];
 
-pub const LINE: &str = "▀";
-pub const BALL: &str = "▄";
-pub const PLAT: &str = "█";
+pub const COLON_WIDTH: u16 = 3;
+pub const COLON: &str = r#"   
+ ▆ 
+   
+ ▆ 
+   "#;
+
+pub const BLOCK_LETTERS: [(char, &str); 33] = [
+    (
+        'A',
+        r#" ▗▆▆▆▖
+ █   █
+ █▆▆▆█
+ █   █
+ ▀   ▀"#,
+    ),
+    (
+        'B',
+        r#" █▀▀▀▙
+ █   █
+ █▀▀▀▙
+ █   █
+ █▄▄▄▛"#,
+    ),
+    (
+        'C',
+        r#" ▗▆▆▆▖
+ █   ▘
+ █    
+ █   ▖
+ ▝▇▇▇▘"#,
+    ),
+    (
+        'D',
+        r#" █▀▀▀▙
+ █   █
+ █   █
+ █   █
+ █▄▄▄▛"#,
+    ),
+    (
+        'E',
+        r#" █▀▀▀▀
+ █    
+ █▀▀▀ 
+ █    
+ █▄▄▄▄"#,
+    ),
+    (
+        'F',
+        r#" █▀▀▀▀
+ █    
+ █▀▀▀ 
+ █    
+ ▀    "#,
+    ),
+    (
+        'G',
+        r#" ▗▆▆▆▖
+ █   ▘
+ █  ▄▖
+ █   █
+ ▝▇▇▇▘"#,
+    ),
+    (
+        'H',
+        r#" █   █
+ █   █
+ █▀▀▀█
+ █   █
+ ▀   ▀"#,
+    ),
+    (
+        'I',
+        r#" ▀▀█▀▀
+   █  
+   █  
+   █  
+ ▄▄█▄▄"#,
+    ),
+    (
+        'J',
+        r#"     █
+     █
+     █
+ ▖   █
+ ▝▇▇▇▘"#,
+    ),
+    (
+        'K',
+        r#" █  ▟▛
+ █ ▟▛ 
+ ██▙  
+ █ ▜▙ 
+ ▀  ▀▘"#,
+    ),
+    (
+        'L',
+        r#" █    
+ █    
+ █    
+ █    
+ █▄▄▄▄"#,
+    ),
+    (
+        'M',
+        r#" █▙ ▟█
+ █▝█▘█
+ █   █
+ █   █
+ ▀   ▀"#,
+    ),
+    (
+        'N',
+        r#" █▙  █
+ █▜▙ █
+ █ ▜▙█
+ █  ▜█
+ ▀   ▀"#,
+    ),
+    (
+        'O',
+        r#" ▗▆▆▆▖
+ █   █
+ █   █
+ █   █
+ ▝▇▇▇▘"#,
+    ),
+    (
+        'P',
+        r#" █▀▀▀▙
+ █   █
+ █▆▆▆▛
+ █    
+ ▀    "#,
+    ),
+    (
+        'Q',
+        r#" ▗▆▆▆▖
+ █   █
+ █   █
+ █ ▚ █
+ ▝▇▇▇▙"#,
+    ),
+    (
+        'R',
+        r#" █▀▀▀▙
+ █   █
+ █▆▆▆▛
+ █  ▜▖
+ ▀   ▀"#,
+    ),
+    (
+        'S',
+        r#" ▗▆▆▆▖
+ █   ▘
+ ▝▀▀▀▖
+ ▖   █
+ ▝▇▇▇▘"#,
+    ),
+    (
+        'T',
+        r#" ▀▀█▀▀
+   █  
+   █  
+   █  
+   ▀  "#,
+    ),
+    (
+        'U',
+        r#" █   █
+ █   █
+ █   █
+ █   █
+ ▝▇▇▇▘"#,
+    ),
+    (
+        'V',
+        r#" █   █
+ █   █
+ ▜▖ ▗▛
+  ▜▄▛ 
+   ▀  "#,
+    ),
+    (
+        'W',
+        r#" █   █
+ █   █
+ █ ▄ █
+ █▟ ▙█
+ ▀   ▀"#,
+    ),
+    (
+        'X',
+        r#" █   █
+ ▝▙ ▟▘
+  ▐█▌ 
+ ▗▛ ▜▖
+ ▀   ▀"#,
+    ),
+    (
+        'Y',
+        r#" █   █
+ ▝▙ ▟▘
+  ▝█▘ 
+   █  
+   ▀  "#,
+    ),
+    (
+        'Z',
+        r#" ▀▀▀▀█
+    ▟▛
+  ▗▛  
+ ▟▛   
+ █▄▄▄▄"#,
+    ),
+    (
+        '!',
+        r#"   █  
+   █  
+   █  
+      
+   ▀  "#,
+    ),
+    (
+        '?',
+        r#" ▗▆▆▆▖
+ ▘   █
+   ▟▛ 
+      
+   ▀  "#,
+    ),
+    (
+        '.',
+        r#"      
+      
+      
+      
+   ▀  "#,
+    ),
+    (
+        ',',
+        r#"      
+      
+      
+   ▄  
+  ▞   "#,
+    ),
+    (
+        '-',
+        r#"      
+      
+ ▀▀▀▀▀
+      
+      "#,
+    ),
+    (
+        '\'',
+        r#"   █  
+   ▀  
+      
+      
+      "#,
+    ),
+    (
+        '/',
+        r#"     █
+    ▟▛
+  ▗▛  
+ ▟▛   
+ ▀    "#,
+    ),
+];
+
+pub const COMPACT_NUMS: [&str; 10] = [
+    "█▀█\n█ █\n▀▀▀",
+    "▀█ \n █ \n▀▀▀",
+    "▀▀█\n█▀▀\n▀▀▀",
+    "▀▀█\n ▀█\n▀▀▀",
+    "█ █\n▀▀█\n  ▀",
+    "█▀▀\n▀▀█\n▀▀▀",
+    "█▀▀\n█▀█\n▀▀▀",
+    "▀▀█\n  █\n  ▀",
+    "█▀█\n█▀█\n▀▀▀",
+    "█▀█\n▀▀█\n▀▀▀",
+];
+
+pub const COMPACT_LETTERS: [(char, &str); 33] = [
+    ('A', "█▀█\n█▀█\n▀ ▀"),
+    ('B', "█▀▄\n█▀▄\n▀▀ "),
+    ('C', "█▀▀\n█  \n▀▀▀"),
+    ('D', "█▀▄\n█ █\n▀▀ "),
+    ('E', "█▀▀\n█▀▀\n▀▀▀"),
+    ('F', "█▀▀\n█▀▀\n▀  "),
+    ('G', "█▀▀\n█ █\n▀▀▀"),
+    ('H', "█ █\n█▀█\n▀ ▀"),
+    ('I', "▀█▀\n █ \n▀▀▀"),
+    ('J', "  █\n▄ █\n▀▀▀"),
+    ('K', "█ █\n█▀▄\n▀ ▀"),
+    ('L', "█  \n█  \n▀▀▀"),
+    ('M', "█▄█\n█▀█\n▀ ▀"),
+    ('N', "█▀█\n█ █\n▀ ▀"),
+    ('O', "█▀█\n█ █\n▀▀▀"),
+    ('P', "█▀█\n█▀▀\n▀  "),
+    ('Q', "█▀█\n█▄█\n  ▀"),
+    ('R', "█▀█\n█▀▄\n▀ ▀"),
+    ('S', "█▀▀\n▀▀█\n▀▀▀"),
+    ('T', "▀█▀\n █ \n ▀ "),
+    ('U', "█ █\n█ █\n▀▀▀"),
+    ('V', "█ █\n█ █\n ▀ "),
+    ('W', "█ █\n█▄█\n▀ ▀"),
+    ('X', "█ █\n▄▀▄\n▀ ▀"),
+    ('Y', "█ █\n █ \n ▀ "),
+    ('Z', "▀▀█\n▄▀ \n▀▀▀"),
+    ('!', " █ \n ▀ \n ▀ "),
+    ('?', "▀▀█\n ▀▀\n ▀ "),
+    ('.', "   \n   \n ▀ "),
+    (',', "   \n ▄ \n▀  "),
+    ('-', "   \n▀▀▀\n   "),
+    ('\'', " █ \n   \n   "),
+    ('/', "  █\n▄▀ \n▀  "),
+];
+
+pub const SEGMENT_NUMS: [&str; 10] = [
+    " _ \n| |\n|_|",
+    "   \n  |\n  |",
+    " _ \n _|\n|_ ",
+    " _ \n _|\n _|",
+    "   \n|_|\n  |",
+    " _ \n|_ \n _|",
+    " _ \n|_ \n|_|",
+    " _ \n  |\n  |",
+    " _ \n|_|\n|_|",
+    " _ \n|_|\n _|",
+];
+
+/// Seven segments can't show every letter, text with any of the others is drawn in `PLAIN`.
+pub const SEGMENT_LETTERS: [(char, &str); 23] = [
+    ('A', " _ \n|_|\n| |"),
+    ('B', "   \n|_ \n|_|"),
+    ('C', " _ \n|  \n|_ "),
+    ('D', "   \n _|\n|_|"),
+    ('E', " _ \n|_ \n|_ "),
+    ('F', " _ \n|_ \n|  "),
+    ('G', " _ \n|  \n|_|"),
+    ('H', "   \n|_|\n| |"),
+    ('I', "   \n | \n | "),
+    ('J', "   \n  |\n|_|"),
+    ('L', "   \n|  \n|_ "),
+    ('N', "   \n _ \n| |"),
+    ('O', " _ \n| |\n|_|"),
+    ('P', " _ \n|_|\n|  "),
+    ('Q', " _ \n|_|\n  |"),
+    ('R', "   \n _ \n|  "),
+    ('S', " _ \n|_ \n _|"),
+    ('T', "   \n|_ \n|_ "),
+    ('U', "   \n| |\n|_|"),
+    ('W', "   \n| |\n|||"),
+    ('Y', "   \n|_|\n _|"),
+    ('-', "   \n _ \n   "),
+    ('.', "   \n   \n . "),
+];
+
+/// Every braille glyph is a 3x4 dot bitmap spread over two cells.
+pub const BRAILLE_NUMS: [&str; 10] = ["⣏⡇", "⣺⡀", "⣩⡂", "⣙⡇", "⠧⡇", "⣛⠅", "⣗⡆", "⢩⠃", "⣟⡇", "⠯⡇"];
+
+pub const BRAILLE_LETTERS: [(char, &str); 33] = [
+    ('A', "⡮⡆"),
+    ('B', "⣟⠆"),
+    ('C', "⢎⡁"),
+    ('D', "⣏⠆"),
+    ('E', "⣟⡁"),
+    ('F', "⡯⠁"),
+    ('G', "⢎⡅"),
+    ('H', "⡗⡇"),
+    ('I', "⣹⡁"),
+    ('J', "⢄⠇"),
+    ('K', "⡷⡁"),
+    ('L', "⣇⡀"),
+    ('M', "⡷⡇"),
+    ('N', "⡏⡆"),
+    ('O', "⢎⠆"),
+    ('P', "⡯⠂"),
+    ('Q', "⢎⡆"),
+    ('R', "⡯⡂"),
+    ('S', "⣊⠅"),
+    ('T', "⢹⠁"),
+    ('U', "⣇⡇"),
+    ('V', "⢇⠇"),
+    ('W', "⡧⡇"),
+    ('X', "⡱⡁"),
+    ('Y', "⢣⠃"),
+    ('Z', "⣍⡃"),
+    ('!', "⢘⠀"),
+    ('?', "⢙⠃"),
+    ('.', "⢀⠀"),
+    (',', "⡠⠀"),
+    ('-', "⠒⠂"),
+    ('\'', "⠘⠀"),
+    ('/', "⡰⠁"),
+];
+
+/// A set of big glyphs for scores, clocks and banners.
+pub struct Font {
+    /// Cells every glyph takes up, the gap to the next one included.
+    pub width: u16,
+    pub height: u16,
+    pub digits: [&'static str; 10],
+    pub letters: &'static [(char, &'static str)],
+    pub colon: &'static str,
+    pub colon_width: u16,
+}
+
+impl Font {
+    /// Returns the glyph of a digit, a colon or a letter, if the font has one. Letters are upper
+    /// case only, lower case ones share their glyphs.
+    pub fn glyph(&self, character: char) -> Option<&'static str> {
+        let character = character.to_ascii_uppercase();
+        match character {
+            '0'..='9' => Some(self.digits[character as usize - '0' as usize]),
+            ':' => Some(self.colon),
+            _ => self
+                .letters
+                .iter()
+                .find(|(letter, _)| *letter == character)
+                .map(|(_, glyph)| *glyph),
+        }
+    }
+
+    /// Whether every character of `text` but spaces has a glyph.
+    pub fn covers(&self, text: &str) -> bool {
+        text.chars()
+            .all(|character| character == ' ' || self.glyph(character).is_some())
+    }
+
+    pub fn text_width(&self, text: &str) -> u16 {
+        text.chars()
+            .map(|character| {
+                if character == ':' {
+                    self.colon_width
+                } else {
+                    self.width
+                }
+            })
+            .sum()
+    }
+}
+
+pub const BLOCK: Font = Font {
+    width: NUM_WIDTH,
+    height: NUM_HEIGHT,
+    digits: NUMS,
+    letters: &BLOCK_LETTERS,
+    colon: COLON,
+    colon_width: COLON_WIDTH,
+};
+
+pub const COMPACT: Font = Font {
+    width: 4,
+    height: 3,
+    digits: COMPACT_NUMS,
+    letters: &COMPACT_LETTERS,
+    colon: "  \n▀ \n▀ ",
+    colon_width: 2,
+};
+
+pub const SEGMENT: Font = Font {
+    width: 4,
+    height: 3,
+    digits: SEGMENT_NUMS,
+    letters: &SEGMENT_LETTERS,
+    colon: "  \n. \n. ",
+    colon_width: 2,
+};
+
+pub const BRAILLE: Font = Font {
+    width: 3,
+    height: 1,
+    digits: BRAILLE_NUMS,
+    letters: &BRAILLE_LETTERS,
+    colon: "⠅ ",
+    colon_width: 2,
+};
+
+/// A single line of text drawn in a big font.
+///
+/// Characters the font has no glyph for are drawn as they are, on the middle row of their cell.
+pub struct BigText<'a> {
+    text: &'a str,
+    font: &'static Font,
+    style: Style,
+    alignment: Alignment,
+}
+
+impl<'a> BigText<'a> {
+    pub fn new(text: &'a str, font: &'static Font) -> Self {
+        Self {
+            text,
+            font,
+            style: Style::default(),
+            alignment: Alignment::Left,
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    pub fn width(&self) -> u16 {
+        self.font.text_width(self.text)
+    }
+}
+
+impl Widget for BigText<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = area.intersection(buf.area);
+        let free = area.width.saturating_sub(self.width());
+        let mut x = area.x
+            + match self.alignment {
+                Alignment::Left => 0,
+                Alignment::Center => free / 2,
+                Alignment::Right => free,
+            };
+
+        for character in self.text.chars() {
+            let width = self.font.text_width(character.encode_utf8(&mut [0; 4]));
+            let lines: Vec<String> = match self.font.glyph(character) {
+                Some(glyph) => glyph.lines().map(str::to_string).collect(),
+                None => {
+                    let mut lines = vec![String::new(); self.font.height as usize];
+                    lines[self.font.height as usize / 2] = character.to_string();
+                    lines
+                }
+            };
+            for (row, line) in lines.iter().enumerate() {
+                let y = area.y + row as u16;
+                if y >= area.bottom() || x >= area.right() {
+                    break;
+                }
+                let room = (area.right() - x).min(width) as usize;
+                buf.set_stringn(x, y, line, room, self.style);
+            }
+            x = x.saturating_add(width);
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FontStyle {
+    /// Picks the biggest font that fits the terminal.
+    Auto,
+    Block,
+    Compact,
+    Segment,
+    Braille,
+}
+
+impl Display for FontStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Auto => write!(f, "  Auto   "),
+            Self::Block => write!(f, "  Block  "),
+            Self::Compact => write!(f, " Compact "),
+            Self::Segment => write!(f, "7 Segment"),
+            Self::Braille => write!(f, " Braille "),
+        }
+    }
+}
+
+impl TryFrom<i8> for FontStyle {
+    type Error = ();
+    fn try_from(value: i8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Auto),
+            1 => Ok(Self::Block),
+            2 => Ok(Self::Compact),
+            3 => Ok(Self::Segment),
+            4 => Ok(Self::Braille),
+            _ => Err(()),
+        }
+    }
+}
+
+impl FontStyle {
+    pub fn len() -> u8 {
+        5
+    }
+
+    /// Returns the font to draw `text` with on a terminal `height` rows tall, when `text` has
+    /// `width` columns to fit in. Only the seven segment and plain fonts are `ascii`, and text
+    /// the seven segments can't spell out is plain rather than a mix of both.
+    pub fn font(&self, height: u16, width: u16, text: &str, ascii: bool) -> &'static Font {
+        if ascii {
+            return if height >= 14 && SEGMENT.text_width(text) <= width && SEGMENT.covers(text) {
+                &SEGMENT
+            } else {
+                &PLAIN
+            };
+        }
+        match *self {
+            Self::Auto => {
+                let by_height: &[&Font] = match height {
+                    24.. => &[&BLOCK, &COMPACT, &BRAILLE],
+                    14.. => &[&COMPACT, &BRAILLE],
+                    _ => &[&BRAILLE],
+                };
+                by_height
+                    .iter()
+                    .find(|font| font.text_width(text) <= width)
+                    .unwrap_or(&&BRAILLE)
+            }
+            Self::Block => &BLOCK,
+            Self::Compact => &COMPACT,
+            Self::Segment if SEGMENT.covers(text) => &SEGMENT,
+            Self::Segment => &PLAIN,
+            Self::Braille => &BRAILLE,
+        }
+    }
+}
+
+pub const PLAIN: Font = Font {
+    width: 1,
+    height: 1,
+    digits: ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"],
+    letters: &[],
+    colon: ":",
+    colon_width: 1,
+};
+
+/// Characters the field and the popups are drawn with.
+pub struct Glyphs {
+    /// One segment of the dashed line down the middle.
+    pub net: &'static str,
+    pub ball: &'static str,
+    /// The ball filling its whole cell, for players who find the normal one too small.
+    pub large_ball: &'static str,
+    pub trail: [&'static str; 3],
+    pub paddle: &'static str,
+    /// First and last cells of left, right, and top and bottom paddles.
+    pub paddle_caps: [(&'static str, &'static str); 3],
+    pub block: &'static str,
+    pub bumper: &'static str,
+    pub brick: &'static str,
+    /// A fresh spark and a fading one.
+    pub sparks: [&'static str; 2],
+    pub lives: &'static str,
+    /// Names of the up, down, left and right arrow keys.
+    pub arrow_keys: [&'static str; 4],
+    /// Marks help that scrolls further up or down.
+    pub up: &'static str,
+    pub down: &'static str,
+    /// Marks a menu that scrolls further up or down.
+    pub more_above: &'static str,
+    pub more_below: &'static str,
+    /// Stands for the start of text cut short.
+    pub ellipsis: &'static str,
+    pub border: border::Set,
+    /// Border of the logo, heavier than the popups.
+    pub frame: border::Set,
+}
+
+pub const UNICODE: Glyphs = Glyphs {
+    net: "▀",
+    ball: "▄",
+    large_ball: "█",
+    trail: ["▄", "▖", "·"],
+    paddle: "█",
+    paddle_caps: [("▟", "▜"), ("▙", "▛"), ("▐", "▌")],
+    block: "█",
+    bumper: "●",
+    brick: "█",
+    sparks: ["•", "·"],
+    lives: "♥",
+    arrow_keys: ["↑", "↓", "←", "→"],
+    up: "↑",
+    down: "↓",
+    more_above: "▲",
+    more_below: "▼",
+    ellipsis: "…",
+    border: border::ROUNDED,
+    frame: border::DOUBLE,
+};
+
+/// For the Linux console and other terminals without block elements or box drawing.
+pub const ASCII: Glyphs = Glyphs {
+    net: ":",
+    ball: "o",
+    large_ball: "O",
+    trail: ["o", ":", "."],
+    paddle: "#",
+    paddle_caps: [("#", "#"), ("#", "#"), ("#", "#")],
+    block: "#",
+    bumper: "@",
+    brick: "=",
+    sparks: ["*", "."],
+    lives: "<3 ",
+    arrow_keys: ["Up", "Down", "Left", "Right"],
+    up: "^",
+    down: "v",
+    more_above: "^",
+    more_below: "v",
+    ellipsis: "~",
+    border: border::Set {
+        top_left: "+",
+        top_right: "+",
+        bottom_left: "+",
+        bottom_right: "+",
+        vertical_left: "|",
+        vertical_right: "|",
+        horizontal_top: "-",
+        horizontal_bottom: "-",
+    },
+    frame: border::Set {
+        top_left: "#",
+        top_right: "#",
+        bottom_left: "#",
+        bottom_right: "#",
+        vertical_left: "#",
+        vertical_right: "#",
+        horizontal_top: "=",
+        horizontal_bottom: "=",
+    },
+};
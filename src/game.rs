@@ -1,56 +1,180 @@
-use rand::Rng;
+use crate::arena::{Arena, Tile};
+use crate::controller::{Intent, Keyboard, PaddleController, Snapshot};
+use crate::difficulty::DifficultyParams;
+use crate::rules::{MatchRules, ServeControl, SERVE_COUNTDOWN};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::VecDeque;
 use std::fmt::Display;
+use std::time::Duration;
 
-#[derive(Clone, Copy)]
-pub enum Difficulty {
-    Easy,
-    Normal,
-    Hard,
-    Insane,
+#[derive(Clone, Copy, PartialEq)]
+pub enum GameMode {
+    Versus,
+    FourPlayer,
+    /// One player rallies against a wall.
+    Practice,
+    /// One player knocks out a wall of bricks.
+    Breakout,
 }
 
-impl Display for Difficulty {
+impl Display for GameMode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match *self {
-            Self::Easy => write!(f, " Easy "),
-            Self::Normal => write!(f, "Normal"),
-            Self::Hard => write!(f, " Hard "),
-            Self::Insane => write!(f, "Insane"),
+            Self::Versus => write!(f, "  Versus  "),
+            Self::FourPlayer => write!(f, "4 Players "),
+            Self::Practice => write!(f, " Practice "),
+            Self::Breakout => write!(f, " Breakout "),
         }
     }
 }
 
-impl TryFrom<i8> for Difficulty {
+impl TryFrom<i8> for GameMode {
     type Error = ();
     fn try_from(value: i8) -> Result<Self, Self::Error> {
         match value {
-            0 => Ok(Self::Easy),
-            1 => Ok(Self::Normal),
-            2 => Ok(Self::Hard),
-            3 => Ok(Self::Insane),
+            0 => Ok(Self::Versus),
+            1 => Ok(Self::FourPlayer),
+            2 => Ok(Self::Practice),
+            3 => Ok(Self::Breakout),
             _ => Err(()),
         }
     }
 }
 
-impl Difficulty {
+impl GameMode {
     pub fn len() -> u8 {
         4
     }
+
+    /// Whether the left player is on their own.
+    pub fn is_solo(&self) -> bool {
+        matches!(*self, Self::Practice | Self::Breakout)
+    }
+
+    /// One line about how the mode is played, for the help window.
+    pub fn description(&self) -> &'static str {
+        match *self {
+            Self::Versus => "Left against right, the classic game.",
+            Self::FourPlayer => "A paddle on every side, the last one with lives wins.",
+            Self::Practice => "Keep the ball in play against three walls.",
+            Self::Breakout => "Knock out the bricks, every miss costs a life.",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Side {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl Display for Side {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Left => write!(f, "Left"),
+            Self::Right => write!(f, "Right"),
+            Self::Top => write!(f, "Top"),
+            Self::Bottom => write!(f, "Bottom"),
+        }
+    }
+}
+
+impl Side {
+    pub const ALL: [Side; 4] = [Self::Left, Self::Right, Self::Top, Self::Bottom];
+
+    pub fn opponent(&self) -> Self {
+        match *self {
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+            Self::Top => Self::Bottom,
+            Self::Bottom => Self::Top,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// Whether the paddle of this side moves up and down.
+    pub fn is_vertical(&self) -> bool {
+        matches!(*self, Self::Left | Self::Right)
+    }
+}
+
+/// Things that happened during a physics step, positions are fractions of the field.
+#[derive(Clone, Copy)]
+pub enum GameEvent {
+    PaddleHit(Side, [f32; 2]),
+    WallBounce,
+    BrickBroken([f32; 2]),
+    /// `scorer` is `None` when nobody touched the ball since the serve, or the conceding side did.
+    Goal {
+        scorer: Option<Side>,
+        conceder: Side,
+    },
 }
 
-pub const PAUSE_MENU_BUTTON_COUNT:usize = 4;
-pub const GAME_OVER_MENU_BUTTON_COUNT:usize = 2;
+pub const MAX_TRAIL_LENGTH: usize = 8;
+
+/// Smallest field width and height the ball is stepped on.
+pub const MIN_FIELD: u16 = 8;
+
+pub const BRICK_COLUMNS: usize = 3;
+pub const BRICK_ROWS: usize = 8;
+/// Fractions of the field width between which the bricks of `GameMode::Breakout` are laid.
+pub const BRICKS_LEFT: f32 = 0.7;
+pub const BRICKS_RIGHT: f32 = 0.92;
+
+pub const PAUSE_MENU_BUTTON_COUNT: usize = 5;
+pub const GAME_OVER_MENU_BUTTON_COUNT: usize = 2;
+pub const PAUSE_MENU_BUTTONS: [&str; PAUSE_MENU_BUTTON_COUNT] =
+    ["Continue", "Help", "Restart", "Save & Quit", "Main Menu"];
+pub const GAME_OVER_MENU_BUTTONS: [&str; GAME_OVER_MENU_BUTTON_COUNT] = ["Restart", "Main Menu"];
 
 pub struct Game {
-    pub difficulty: i8,
-    pub win_score: u16,
-    pub r_pos: f32,
-    pub l_pos: f32,
+    pub difficulty: DifficultyParams,
+    pub mode: GameMode,
+    pub rules: MatchRules,
+    pub arena: Arena,
+    /// Every random choice of the match comes from here, so a seeded match plays out the same.
+    pub rng: StdRng,
+    /// Physics steps since the match started, drives the moving blocks of the arena.
+    pub ticks: u64,
+    /// Set after a portal moved the ball, until it leaves the exit portal.
+    pub teleported: bool,
+    /// Width and height of the field in cells.
+    pub field: (u16, u16),
+    /// Paddle positions indexed by `Side`, from 0.0 to 1.0 along their track.
+    pub paddles: [f32; 4],
+    /// What moves every paddle, indexed by `Side`.
+    pub controllers: [Box<dyn PaddleController>; 4],
+    /// Names typed in the setup menu, indexed by `Side`.
+    pub names: [Option<String>; 4],
     pub b_pos: [f32; 2],
     pub b_vel: [i16; 2],
-    pub r_score: u16,
-    pub l_score: u16,
+    /// Steps per second the ball moves at, sped up by every paddle hit of a rally.
+    pub speed: u16,
+    /// The previous ball positions, newest first.
+    pub trail: VecDeque<[f32; 2]>,
+    pub scores: [u16; 4],
+    /// Goals each side can still concede, unused in `GameMode::Versus`.
+    pub lives: [u16; 4],
+    /// Returns in a row since the left player last missed, in solo modes.
+    pub streak: u16,
+    pub best_streak: u16,
+    /// Which bricks are still standing in `GameMode::Breakout`, row by row.
+    pub bricks: Vec<bool>,
+    pub last_hitter: Option<Side>,
+    pub first_server: Side,
+    pub last_point_loser: Option<Side>,
+    pub server: Side,
+    pub serve_timer: Duration,
+    pub awaiting_serve: bool,
+    pub elapsed: Duration,
+    pub events: Vec<GameEvent>,
     pub help_popup_shown: bool,
     pub game_paused: bool,
     pub pause_menu_selected_button: i8,
@@ -61,14 +185,34 @@ pub struct Game {
 impl Default for Game {
     fn default() -> Self {
         Self {
-            difficulty: Difficulty::Easy as i8,
-            win_score: 0,
-            r_pos: 0.0,
-            l_pos: 0.0,
+            difficulty: DifficultyParams::default(),
+            mode: GameMode::Versus,
+            rules: MatchRules::default(),
+            arena: Arena::default(),
+            rng: StdRng::from_entropy(),
+            ticks: 0,
+            teleported: false,
+            field: (0, 0),
+            paddles: [0.0; 4],
+            controllers: [(); 4].map(|_| Box::new(Keyboard) as Box<dyn PaddleController>),
+            names: Default::default(),
             b_pos: [0.0; 2],
             b_vel: [0; 2],
-            r_score: 0,
-            l_score: 0,
+            speed: 0,
+            trail: VecDeque::with_capacity(MAX_TRAIL_LENGTH),
+            scores: [0; 4],
+            lives: [0; 4],
+            streak: 0,
+            best_streak: 0,
+            bricks: vec![],
+            last_hitter: None,
+            first_server: Side::Left,
+            last_point_loser: None,
+            server: Side::Left,
+            serve_timer: Duration::ZERO,
+            awaiting_serve: false,
+            elapsed: Duration::ZERO,
+            events: vec![],
             help_popup_shown: false,
             game_paused: false,
             pause_menu_selected_button: 0,
@@ -79,16 +223,41 @@ impl Default for Game {
 }
 
 impl Game {
-    pub fn new(_difficulty: i8, _win_score: u16) -> Self {
+    pub fn new(
+        _difficulty: DifficultyParams,
+        _mode: GameMode,
+        _rules: MatchRules,
+        _arena: Arena,
+    ) -> Self {
         Self {
             difficulty: _difficulty,
-            win_score: _win_score,
-            r_pos: 0.5,
-            l_pos: 0.5,
+            mode: _mode,
+            rules: _rules,
+            arena: _arena,
+            rng: StdRng::from_entropy(),
+            ticks: 0,
+            teleported: false,
+            field: (0, 0),
+            paddles: [0.5; 4],
+            controllers: [(); 4].map(|_| Box::new(Keyboard) as Box<dyn PaddleController>),
+            names: Default::default(),
             b_pos: [0.5; 2],
             b_vel: [1; 2],
-            r_score: 0,
-            l_score: 0,
+            speed: _difficulty.ball_speed,
+            trail: VecDeque::with_capacity(MAX_TRAIL_LENGTH),
+            scores: [0; 4],
+            lives: [0; 4],
+            streak: 0,
+            best_streak: 0,
+            bricks: vec![],
+            last_hitter: None,
+            first_server: Side::Left,
+            last_point_loser: None,
+            server: Side::Left,
+            serve_timer: Duration::ZERO,
+            awaiting_serve: false,
+            elapsed: Duration::ZERO,
+            events: vec![],
             help_popup_shown: false,
             game_paused: false,
             pause_menu_selected_button: 0,
@@ -100,20 +269,458 @@ impl Game {
     pub fn reset(&mut self) {
         self.b_pos = [0.5; 2];
         self.b_vel = [1; 2];
-        if rand::random() {
-            self.b_vel[0] *= -1;
+        self.speed = self.difficulty.ball_speed;
+        self.trail.clear();
+        self.last_hitter = None;
+        self.teleported = false;
+        if self.mode == GameMode::FourPlayer {
+            // Nobody owns the serve with four players, so the ball heads for a random corner.
+            if self.rng.gen() {
+                self.b_vel[0] *= -1;
+            }
+            self.serve_timer = SERVE_COUNTDOWN;
+        } else {
+            self.server = if self.mode.is_solo() {
+                Side::Left
+            } else {
+                self.rules
+                    .server(
+                        self.first_server,
+                        self.points_played(),
+                        self.last_point_loser,
+                    )
+                    .unwrap_or(if self.rng.gen() {
+                        Side::Left
+                    } else {
+                        Side::Right
+                    })
+            };
+            // The ball always travels from the server towards the receiver.
+            if self.server == Side::Right {
+                self.b_vel[0] = -1;
+            }
+            // Computer players don't press keys, so they always serve after the countdown.
+            match self.rules.serve_control {
+                ServeControl::KeyPress if self.controllers[self.server.index()].takes_keys() => {
+                    self.awaiting_serve = true
+                }
+                _ => self.serve_timer = SERVE_COUNTDOWN,
+            }
         }
-        if rand::random() {
+        if self.rng.gen() {
             self.b_vel[1] *= -1;
         }
-        self.b_pos[1] = rand::thread_rng().gen::<f32>() / 2.0 + 0.25;
+        self.b_pos[1] = self.rng.gen::<f32>() / 2.0 + 0.25;
         self.game_paused = false;
     }
 
     pub fn full_reset(&mut self) {
+        self.scores = [0; 4];
+        self.lives = [self.rules.win_score; 4];
+        for side in Side::ALL {
+            if self.mode == GameMode::Versus {
+                self.scores[side.index()] = self.head_start(side);
+            } else {
+                self.lives[side.index()] += self.rules.handicap(side).bonus;
+            }
+        }
+        self.streak = 0;
+        self.best_streak = 0;
+        self.bricks = if self.mode == GameMode::Breakout {
+            vec![true; BRICK_COLUMNS * BRICK_ROWS]
+        } else {
+            vec![]
+        };
+        self.paddles = [0.5; 4];
+        self.elapsed = Duration::ZERO;
+        self.ticks = 0;
+        self.last_point_loser = None;
+        self.first_server = if self.rng.gen() {
+            Side::Left
+        } else {
+            Side::Right
+        };
         self.reset();
-        self.l_score = 0;
-        self.r_score = 0;
+    }
+
+    /// Points `side` starts a versus match with.
+    fn head_start(&self, side: Side) -> u16 {
+        // A head start never wins the match on its own.
+        let bonus = self.rules.handicap(side).bonus;
+        bonus.min(self.rules.win_score.saturating_sub(1))
+    }
+
+    /// Points played in a versus match so far, head starts left out.
+    pub fn points_played(&self) -> u16 {
+        [Side::Left, Side::Right]
+            .map(|side| self.score(side).saturating_sub(self.head_start(side)))
+            .iter()
+            .sum()
+    }
+
+    pub fn score(&self, side: Side) -> u16 {
+        self.scores[side.index()]
+    }
+
+    pub fn score_point(&mut self, side: Side) {
+        self.scores[side.index()] += 1;
+        self.last_point_loser = Some(side.opponent());
+        self.events.push(GameEvent::Goal {
+            scorer: Some(side),
+            conceder: side.opponent(),
+        });
+        self.reset();
+    }
+
+    /// The ball went past the paddle of `side`.
+    pub fn concede(&mut self, side: Side) {
+        if self.mode == GameMode::Versus {
+            self.score_point(side.opponent());
+            return;
+        }
+
+        let scorer = self.last_hitter.filter(|hitter| *hitter != side);
+        if let Some(scorer) = scorer {
+            self.scores[scorer.index()] += 1;
+        }
+        self.lives[side.index()] = self.lives[side.index()].saturating_sub(1);
+        self.streak = 0;
+        self.events.push(GameEvent::Goal {
+            scorer,
+            conceder: side,
+        });
+        self.reset();
+    }
+
+    /// Whether the edge behind `side` bounces the ball instead of being a goal.
+    pub fn is_wall(&self, side: Side) -> bool {
+        match self.mode {
+            GameMode::Versus => !side.is_vertical(),
+            GameMode::FourPlayer => self.lives[side.index()] == 0,
+            GameMode::Practice | GameMode::Breakout => side != Side::Left,
+        }
+    }
+
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.field = (width, height);
+    }
+
+    /// Length of the paddle of `side` in cells, caps included.
+    pub fn paddle_length(&self, side: Side) -> u16 {
+        let length = self.difficulty.paddle_length as i16 + self.rules.handicap(side).length as i16;
+        length.max(2) as u16
+    }
+
+    /// Returns the first cell and the length of the stretch a paddle of `side` slides along.
+    ///
+    /// With four players the corners are kept clear so paddles never overlap.
+    pub fn paddle_track(&self, side: Side) -> (u16, u16) {
+        let (width, height) = self.field;
+        let track = if side.is_vertical() { height } else { width };
+        match self.mode {
+            GameMode::Versus | GameMode::Practice | GameMode::Breakout => (0, track),
+            GameMode::FourPlayer => (2, track.saturating_sub(4)),
+        }
+    }
+
+    /// Returns the first cell covered by the paddle of `side` along its track.
+    pub fn paddle_start(&self, side: Side) -> u16 {
+        let (start, length) = self.paddle_track(side);
+        let range = length.saturating_sub(self.paddle_length(side));
+        start + (self.paddles[side.index()] * range as f32).round() as u16
+    }
+
+    /// Returns the column of a vertical paddle, or the row of a horizontal one.
+    pub fn paddle_line(&self, side: Side) -> u16 {
+        let (width, height) = self.field;
+        match side {
+            Side::Left => 1,
+            Side::Right => width.saturating_sub(2),
+            Side::Top => 1,
+            Side::Bottom => height.saturating_sub(2),
+        }
+    }
+
+    /// Moves the paddle of `side` for a key press, paddles of computer players ignore it.
+    pub fn move_paddle(&mut self, side: Side, cells: i16) {
+        if self.controllers[side.index()].takes_keys() {
+            self.slide_paddle(side, cells);
+        }
+    }
+
+    fn slide_paddle(&mut self, side: Side, cells: i16) {
+        if self.game_paused || self.game_over || self.is_wall(side) {
+            return;
+        }
+
+        let range = self
+            .paddle_track(side)
+            .1
+            .saturating_sub(self.paddle_length(side)) as f32;
+        if range <= 0.0 {
+            return;
+        }
+        let cells =
+            cells * self.difficulty.paddle_speed as i16 * self.rules.handicap(side).speed as i16;
+        let pos = &mut self.paddles[side.index()];
+        *pos = ((*pos * range).round() + cells as f32).max(0.0).min(range) / range;
+    }
+
+    /// Moves the ball one cell, bouncing it off paddles and walls or scoring a goal.
+    pub fn step(&mut self) {
+        let (width, height) = self.field;
+        if self.game_paused
+            || self.game_over
+            || !self.is_ball_in_play()
+            || width < MIN_FIELD
+            || height < MIN_FIELD
+        {
+            return;
+        }
+
+        self.ticks += 1;
+        for side in Side::ALL {
+            if self.is_wall(side) {
+                continue;
+            }
+            let snapshot = self.snapshot(side);
+            match self.controllers[side.index()].update(&snapshot) {
+                Intent::Up => self.slide_paddle(side, -1),
+                Intent::Down => self.slide_paddle(side, 1),
+                Intent::Stay => (),
+            }
+        }
+
+        let (max_x, max_y) = (width as i16 - 1, height as i16 - 1);
+        let prev = (
+            (self.b_pos[0] * max_x as f32).round() as i16,
+            (self.b_pos[1] * max_y as f32).round() as i16,
+        );
+        let mut b_pos = [prev.0 + self.b_vel[0], prev.1 + self.b_vel[1]];
+        let normalize =
+            |pos: [i16; 2]| [pos[0] as f32 / max_x as f32, pos[1] as f32 / max_y as f32];
+
+        // Collision detection, a paddle returns the ball when it is right in front of it.
+        for side in Side::ALL {
+            if self.is_wall(side) {
+                continue;
+            }
+
+            let (along, across, vel) = if side.is_vertical() {
+                (b_pos[1], b_pos[0], self.b_vel[0])
+            } else {
+                (b_pos[0], b_pos[1], self.b_vel[1])
+            };
+            let line = self.paddle_line(side) as i16;
+            let (front, incoming) = match side {
+                Side::Left | Side::Top => (line + 1, vel < 0),
+                Side::Right | Side::Bottom => (line - 1, vel > 0),
+            };
+            let start = self.paddle_start(side) as i16;
+
+            if incoming
+                && across == front
+                && (start..start + self.paddle_length(side) as i16).contains(&along)
+            {
+                if side.is_vertical() {
+                    self.b_vel[0] *= -1;
+                } else {
+                    self.b_vel[1] *= -1;
+                }
+                self.last_hitter = Some(side);
+                self.speed = (self.speed + self.difficulty.acceleration)
+                    .min(self.difficulty.max_speed.max(self.difficulty.ball_speed));
+                if self.mode.is_solo() {
+                    self.return_ball();
+                }
+                self.events
+                    .push(GameEvent::PaddleHit(side, normalize(b_pos)));
+            }
+        }
+
+        if let Some(brick) = self.brick_at((b_pos[0], b_pos[1])) {
+            let hit_x = self.brick_at((b_pos[0], prev.1)).is_some();
+            let hit_y = self.brick_at((prev.0, b_pos[1])).is_some();
+            if hit_x || !hit_y {
+                self.b_vel[0] *= -1;
+            }
+            if hit_y || !hit_x {
+                self.b_vel[1] *= -1;
+            }
+            self.break_brick(brick, normalize(b_pos));
+            b_pos = [prev.0, prev.1];
+        }
+
+        let tile_at = |pos: (i16, i16)| self.arena.tile_at(pos, self.field, self.ticks);
+        // A moving block may slide over the ball, which is then let out the way it was going.
+        let buried = matches!(tile_at(prev), Tile::Block | Tile::Bumper);
+        match tile_at((b_pos[0], b_pos[1])) {
+            _ if buried => (),
+            Tile::Block => {
+                // Bounce off whichever faces of the block the ball ran into.
+                let hit_x = tile_at((b_pos[0], prev.1)) == Tile::Block;
+                let hit_y = tile_at((prev.0, b_pos[1])) == Tile::Block;
+                if hit_x || !hit_y {
+                    self.b_vel[0] *= -1;
+                }
+                if hit_y || !hit_x {
+                    self.b_vel[1] *= -1;
+                }
+                b_pos = [prev.0, prev.1];
+                self.events.push(GameEvent::WallBounce);
+            }
+            Tile::Bumper => {
+                self.b_vel = [-self.b_vel[0], -self.b_vel[1]];
+                b_pos = [prev.0, prev.1];
+                self.events.push(GameEvent::WallBounce);
+            }
+            Tile::Portal(letter) => {
+                if !self.teleported {
+                    let exit = self
+                        .arena
+                        .portal_exit(letter, (b_pos[0], b_pos[1]), self.field);
+                    b_pos = [exit.0 as i16, exit.1 as i16];
+                    self.teleported = true;
+                }
+            }
+            Tile::Empty => self.teleported = false,
+        }
+
+        let edges = [
+            (Side::Left, b_pos[0] <= 0, b_pos[1] as f32 / max_y as f32),
+            (
+                Side::Right,
+                b_pos[0] >= max_x,
+                b_pos[1] as f32 / max_y as f32,
+            ),
+            (Side::Top, b_pos[1] <= 0, b_pos[0] as f32 / max_x as f32),
+            (
+                Side::Bottom,
+                b_pos[1] >= max_y,
+                b_pos[0] as f32 / max_x as f32,
+            ),
+        ];
+        for (side, reached, along) in edges {
+            if reached && !self.is_wall(side) && self.arena.is_goal(along) {
+                self.concede(side);
+                return;
+            }
+        }
+        for (side, reached, _) in edges {
+            if reached {
+                if side.is_vertical() {
+                    self.b_vel[0] *= -1;
+                } else {
+                    self.b_vel[1] *= -1;
+                }
+                self.events.push(GameEvent::WallBounce);
+            }
+        }
+        // An obstacle can send the ball back at the edge it just bounced off.
+        b_pos = [b_pos[0].clamp(0, max_x), b_pos[1].clamp(0, max_y)];
+
+        self.move_ball(normalize(b_pos));
+    }
+
+    /// Returns what the controller of `side` gets to see of the match.
+    pub fn snapshot(&self, side: Side) -> Snapshot {
+        let (width, height) = self.field;
+        Snapshot {
+            side,
+            tick: self.ticks,
+            field: self.field,
+            ball: [
+                (self.b_pos[0] * (width - 1) as f32).round() as i16,
+                (self.b_pos[1] * (height - 1) as f32).round() as i16,
+            ],
+            ball_vel: self.b_vel,
+            paddles: Side::ALL.map(|side| self.paddle_start(side)),
+            paddle_length: self.paddle_length(side),
+            paddle_lines: Side::ALL.map(|side| self.paddle_line(side)),
+            scores: self.scores,
+        }
+    }
+
+    /// Counts a return of the left player in solo modes, only practice scores them.
+    fn return_ball(&mut self) {
+        self.streak += 1;
+        self.best_streak = self.best_streak.max(self.streak);
+        if self.mode == GameMode::Practice {
+            self.scores[Side::Left.index()] += 1;
+        }
+    }
+
+    /// Returns the index of the standing brick covering the field cell `pos`.
+    pub fn brick_at(&self, pos: (i16, i16)) -> Option<usize> {
+        let (width, height) = self.field;
+        let x = (pos.0 as f32 / width as f32 - BRICKS_LEFT) / (BRICKS_RIGHT - BRICKS_LEFT);
+        let y = pos.1 as f32 / height as f32;
+        if !(0.0..1.0).contains(&x) || !(0.0..1.0).contains(&y) {
+            return None;
+        }
+
+        let index =
+            (y * BRICK_ROWS as f32) as usize * BRICK_COLUMNS + (x * BRICK_COLUMNS as f32) as usize;
+        self.bricks
+            .get(index)
+            .copied()
+            .unwrap_or(false)
+            .then_some(index)
+    }
+
+    /// Knocks out a brick, the wall is rebuilt once the last one falls.
+    fn break_brick(&mut self, brick: usize, pos: [f32; 2]) {
+        self.bricks[brick] = false;
+        self.scores[Side::Left.index()] += 1;
+        self.events.push(GameEvent::BrickBroken(pos));
+        if !self.bricks.contains(&true) {
+            self.bricks.fill(true);
+        }
+    }
+
+    /// Moves the ball, remembering where it was for the trail.
+    pub fn move_ball(&mut self, pos: [f32; 2]) {
+        if self.trail.len() == MAX_TRAIL_LENGTH {
+            self.trail.pop_back();
+        }
+        self.trail.push_front(self.b_pos);
+        self.b_pos = pos;
+    }
+
+    /// Time between two physics steps at the current ball speed.
+    pub fn step_time(&self) -> Duration {
+        Duration::from_secs_f32(1.0 / self.speed.max(1) as f32)
+    }
+
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Runs down the serve countdown, or the match clock once the ball is in play.
+    pub fn advance_clock(&mut self, delta: Duration) {
+        if self.awaiting_serve {
+            return;
+        }
+
+        if self.serve_timer.is_zero() {
+            self.elapsed += delta;
+        } else {
+            self.serve_timer = self.serve_timer.saturating_sub(delta);
+        }
+    }
+
+    pub fn is_ball_in_play(&self) -> bool {
+        !self.awaiting_serve && self.serve_timer.is_zero()
+    }
+
+    pub fn serve(&mut self, side: Side) {
+        if self.awaiting_serve && self.server == side {
+            self.awaiting_serve = false;
+        }
+    }
+
+    pub fn time_left(&self) -> Duration {
+        self.rules.time_limit.saturating_sub(self.elapsed)
     }
 
     pub fn pause_game(&mut self) {
@@ -146,12 +753,44 @@ impl Game {
     }
 
     pub fn check_game_over(&mut self) {
-        if self.l_score.max(self.r_score) >= self.win_score {
+        let over = if self.mode.is_solo() {
+            self.lives[Side::Left.index()] == 0
+        } else {
+            self.winner().is_some()
+        };
+        if over {
             self.game_over = true;
             self.game_paused = false;
-        } 
+        }
+    }
+
+    /// Name of the player of `side`, the side itself when they didn't type one.
+    pub fn player_name(&self, side: Side) -> String {
+        self.names[side.index()]
+            .clone()
+            .unwrap_or_else(|| side.to_string())
     }
-    
+
+    pub fn winner(&self) -> Option<Side> {
+        match self.mode {
+            GameMode::Versus => self.rules.winner(
+                self.score(Side::Left),
+                self.score(Side::Right),
+                self.elapsed,
+            ),
+            GameMode::FourPlayer => {
+                let mut alive = Side::ALL
+                    .into_iter()
+                    .filter(|side| self.lives[side.index()] > 0);
+                match (alive.next(), alive.next()) {
+                    (Some(side), None) => Some(side),
+                    _ => None,
+                }
+            }
+            GameMode::Practice | GameMode::Breakout => None,
+        }
+    }
+
     pub fn inc_game_over_menu_button(&mut self) {
         self.game_over_menu_selected_button += 1;
         if self.game_over_menu_selected_button >= GAME_OVER_MENU_BUTTON_COUNT as i8 {
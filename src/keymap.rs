@@ -0,0 +1,555 @@
+use crate::app::App;
+use crate::font::Glyphs;
+use crate::game::Side;
+use crate::menu;
+use crate::rules::ServeControl;
+use crate::text_input::Edit;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fmt::Display;
+
+/// What the keys act on, worked out from the menus and popups the app is showing.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Screen {
+    MainMenu,
+    Settings,
+    Setup,
+    /// The custom difficulty editor.
+    CustomDifficulty,
+    Playing,
+    Paused,
+    GameOver,
+    Help,
+    /// A yes or no question over any other screen.
+    Confirm,
+    /// Typing into a text item of a menu.
+    TextInput,
+}
+
+impl Display for Screen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::MainMenu => write!(f, "Main Menu"),
+            Self::Settings => write!(f, "Settings"),
+            Self::Setup => write!(f, "Match Setup"),
+            Self::CustomDifficulty => write!(f, "Custom Difficulty"),
+            Self::Playing => write!(f, "In A Match"),
+            Self::Paused => write!(f, "Pause Menu"),
+            Self::GameOver => write!(f, "Game Over"),
+            Self::Help => write!(f, "Help"),
+            Self::Confirm => write!(f, "Confirm"),
+            Self::TextInput => write!(f, "Typing"),
+        }
+    }
+}
+
+impl Screen {
+    pub fn current(app: &App) -> Self {
+        if app.confirm.is_some() {
+            Self::Confirm
+        } else if app.game_started {
+            if app.game.help_popup_shown {
+                Self::Help
+            } else if app.game.game_over {
+                Self::GameOver
+            } else if app.game.game_paused {
+                Self::Paused
+            } else {
+                Self::Playing
+            }
+        } else if menu::shown(app).is_some_and(|menu| menu.is_editing(app)) {
+            Self::TextInput
+        } else if app.settings_menu_shown {
+            Self::Settings
+        } else if app.custom_menu_shown {
+            Self::CustomDifficulty
+        } else if app.difficulty_menu_shown {
+            Self::Setup
+        } else if app.help_popup_shown {
+            Self::Help
+        } else {
+            Self::MainMenu
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Action {
+    /// Quits, asking first when a match would be lost.
+    Quit,
+    ForceQuit,
+    /// Goes back to the main menu.
+    Back,
+    Accept,
+    Cancel,
+    /// Moves the cursor of the text being typed or deletes from it.
+    Edit(Edit),
+    CloseHelp,
+    ScrollUp,
+    ScrollDown,
+    PageUp,
+    PageDown,
+    /// Moves the selection to the previous button or choice of a menu.
+    MenuPrev,
+    MenuNext,
+    /// Changes the selected choice of a menu.
+    ChoicePrev,
+    ChoiceNext,
+    Select,
+    MoveUp(Side),
+    MoveDown(Side),
+    Serve(Side),
+    Pause,
+    Resume,
+    Help,
+    Mute,
+}
+
+impl Action {
+    /// Whether the action does anything in the current match, paddle keys only work for the
+    /// sides people play. Outside of a match every action is available.
+    pub fn is_available(&self, app: &App) -> bool {
+        if !app.game_started {
+            return true;
+        }
+        match *self {
+            Self::MoveUp(side) | Self::MoveDown(side) => {
+                !app.game.is_wall(side) && app.game.controllers[side.index()].takes_keys()
+            }
+            Self::Serve(side) => {
+                app.game.rules.serve_control == ServeControl::KeyPress
+                    && app.game.controllers[side.index()].takes_keys()
+            }
+            _ => true,
+        }
+    }
+}
+
+/// A key, pressed with Ctrl or on its own.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub ctrl: bool,
+}
+
+const fn key(code: KeyCode) -> Key {
+    Key { code, ctrl: false }
+}
+
+const fn ctrl(code: KeyCode) -> Key {
+    Key { code, ctrl: true }
+}
+
+impl Key {
+    fn matches(&self, event: KeyEvent) -> bool {
+        self.code == event.code && self.ctrl == event.modifiers.contains(KeyModifiers::CONTROL)
+    }
+}
+
+pub struct Binding {
+    pub keys: &'static [Key],
+    pub screens: &'static [Screen],
+    pub action: Action,
+    /// Short name shown in the footer, bindings sharing one are shown together. Empty leaves the
+    /// binding out of the footer.
+    pub hint: &'static str,
+    /// What the help window says the binding does.
+    pub description: &'static str,
+}
+
+const MENUS: &[Screen] = &[
+    Screen::MainMenu,
+    Screen::Settings,
+    Screen::Setup,
+    Screen::CustomDifficulty,
+];
+const CHOICE_MENUS: &[Screen] = &[Screen::Settings, Screen::Setup, Screen::CustomDifficulty];
+const BUTTON_ROWS: &[Screen] = &[Screen::Paused, Screen::GameOver, Screen::Confirm];
+const MATCH: &[Screen] = &[Screen::Playing, Screen::Paused, Screen::GameOver];
+const ALL: &[Screen] = &[
+    Screen::MainMenu,
+    Screen::Settings,
+    Screen::Setup,
+    Screen::CustomDifficulty,
+    Screen::Playing,
+    Screen::Paused,
+    Screen::GameOver,
+    Screen::Help,
+    Screen::Confirm,
+    Screen::TextInput,
+];
+
+/// Every key the app reacts to. The first binding for a key and screen wins.
+pub const BINDINGS: &[Binding] = &[
+    Binding {
+        keys: &[key(KeyCode::Enter)],
+        screens: &[Screen::TextInput],
+        action: Action::Accept,
+        hint: "Done",
+        description: "Keep the typed text",
+    },
+    Binding {
+        keys: &[key(KeyCode::Esc)],
+        screens: &[Screen::TextInput],
+        action: Action::Cancel,
+        hint: "Cancel",
+        description: "Throw the typed text away",
+    },
+    Binding {
+        keys: &[key(KeyCode::Left)],
+        screens: &[Screen::TextInput],
+        action: Action::Edit(Edit::Left),
+        hint: "Cursor",
+        description: "Move the cursor left",
+    },
+    Binding {
+        keys: &[key(KeyCode::Right)],
+        screens: &[Screen::TextInput],
+        action: Action::Edit(Edit::Right),
+        hint: "Cursor",
+        description: "Move the cursor right",
+    },
+    Binding {
+        keys: &[key(KeyCode::Home)],
+        screens: &[Screen::TextInput],
+        action: Action::Edit(Edit::Home),
+        hint: "",
+        description: "Move the cursor to the start",
+    },
+    Binding {
+        keys: &[key(KeyCode::End)],
+        screens: &[Screen::TextInput],
+        action: Action::Edit(Edit::End),
+        hint: "",
+        description: "Move the cursor to the end",
+    },
+    Binding {
+        keys: &[key(KeyCode::Backspace)],
+        screens: &[Screen::TextInput],
+        action: Action::Edit(Edit::Backspace),
+        hint: "Delete",
+        description: "Delete the character before the cursor",
+    },
+    Binding {
+        keys: &[key(KeyCode::Delete)],
+        screens: &[Screen::TextInput],
+        action: Action::Edit(Edit::Delete),
+        hint: "Delete",
+        description: "Delete the character under the cursor",
+    },
+    Binding {
+        keys: &[key(KeyCode::Char('y'))],
+        screens: &[Screen::Confirm],
+        action: Action::Accept,
+        hint: "Yes",
+        description: "Answer yes",
+    },
+    Binding {
+        keys: &[key(KeyCode::Char('n')), key(KeyCode::Esc)],
+        screens: &[Screen::Confirm],
+        action: Action::Cancel,
+        hint: "No",
+        description: "Answer no",
+    },
+    Binding {
+        keys: &[key(KeyCode::Up)],
+        screens: &[Screen::Help],
+        action: Action::ScrollUp,
+        hint: "Scroll",
+        description: "Scroll up",
+    },
+    Binding {
+        keys: &[key(KeyCode::Down)],
+        screens: &[Screen::Help],
+        action: Action::ScrollDown,
+        hint: "Scroll",
+        description: "Scroll down",
+    },
+    Binding {
+        keys: &[key(KeyCode::PageUp)],
+        screens: &[Screen::Help],
+        action: Action::PageUp,
+        hint: "",
+        description: "Scroll up a page",
+    },
+    Binding {
+        keys: &[key(KeyCode::PageDown)],
+        screens: &[Screen::Help],
+        action: Action::PageDown,
+        hint: "",
+        description: "Scroll down a page",
+    },
+    Binding {
+        keys: &[key(KeyCode::Esc)],
+        screens: &[Screen::Help],
+        action: Action::CloseHelp,
+        hint: "Close",
+        description: "Close the help",
+    },
+    Binding {
+        keys: &[key(KeyCode::Up)],
+        screens: MENUS,
+        action: Action::MenuPrev,
+        hint: "Move",
+        description: "Select the previous item",
+    },
+    Binding {
+        keys: &[key(KeyCode::Down)],
+        screens: MENUS,
+        action: Action::MenuNext,
+        hint: "Move",
+        description: "Select the next item",
+    },
+    Binding {
+        keys: &[key(KeyCode::Left)],
+        screens: CHOICE_MENUS,
+        action: Action::ChoicePrev,
+        hint: "Change",
+        description: "Pick the previous option",
+    },
+    Binding {
+        keys: &[key(KeyCode::Right)],
+        screens: CHOICE_MENUS,
+        action: Action::ChoiceNext,
+        hint: "Change",
+        description: "Pick the next option",
+    },
+    Binding {
+        keys: &[key(KeyCode::Left)],
+        screens: BUTTON_ROWS,
+        action: Action::MenuPrev,
+        hint: "Move",
+        description: "Select the previous button",
+    },
+    Binding {
+        keys: &[key(KeyCode::Right)],
+        screens: BUTTON_ROWS,
+        action: Action::MenuNext,
+        hint: "Move",
+        description: "Select the next button",
+    },
+    Binding {
+        keys: &[key(KeyCode::Enter)],
+        screens: &[
+            Screen::MainMenu,
+            Screen::Settings,
+            Screen::Setup,
+            Screen::CustomDifficulty,
+            Screen::Paused,
+            Screen::GameOver,
+            Screen::Confirm,
+        ],
+        action: Action::Select,
+        hint: "Select",
+        description: "Press the selected button, flip a toggle or start typing",
+    },
+    Binding {
+        keys: &[key(KeyCode::Char('w'))],
+        screens: &[Screen::Playing],
+        action: Action::MoveUp(Side::Left),
+        hint: "Left",
+        description: "Move the left paddle up",
+    },
+    Binding {
+        keys: &[key(KeyCode::Char('s'))],
+        screens: &[Screen::Playing],
+        action: Action::MoveDown(Side::Left),
+        hint: "Left",
+        description: "Move the left paddle down",
+    },
+    Binding {
+        keys: &[key(KeyCode::Up)],
+        screens: &[Screen::Playing],
+        action: Action::MoveUp(Side::Right),
+        hint: "Right",
+        description: "Move the right paddle up",
+    },
+    Binding {
+        keys: &[key(KeyCode::Down)],
+        screens: &[Screen::Playing],
+        action: Action::MoveDown(Side::Right),
+        hint: "Right",
+        description: "Move the right paddle down",
+    },
+    Binding {
+        keys: &[key(KeyCode::Char('z'))],
+        screens: &[Screen::Playing],
+        action: Action::MoveUp(Side::Top),
+        hint: "Top",
+        description: "Move the top paddle left",
+    },
+    Binding {
+        keys: &[key(KeyCode::Char('x'))],
+        screens: &[Screen::Playing],
+        action: Action::MoveDown(Side::Top),
+        hint: "Top",
+        description: "Move the top paddle right",
+    },
+    Binding {
+        keys: &[key(KeyCode::Char(','))],
+        screens: &[Screen::Playing],
+        action: Action::MoveUp(Side::Bottom),
+        hint: "Bottom",
+        description: "Move the bottom paddle left",
+    },
+    Binding {
+        keys: &[key(KeyCode::Char('.'))],
+        screens: &[Screen::Playing],
+        action: Action::MoveDown(Side::Bottom),
+        hint: "Bottom",
+        description: "Move the bottom paddle right",
+    },
+    Binding {
+        keys: &[key(KeyCode::Char('d'))],
+        screens: &[Screen::Playing],
+        action: Action::Serve(Side::Left),
+        hint: "Serve",
+        description: "Serve for the left player",
+    },
+    Binding {
+        keys: &[key(KeyCode::Left)],
+        screens: &[Screen::Playing],
+        action: Action::Serve(Side::Right),
+        hint: "Serve",
+        description: "Serve for the right player",
+    },
+    Binding {
+        keys: &[key(KeyCode::Char('p')), key(KeyCode::Esc)],
+        screens: &[Screen::Playing],
+        action: Action::Pause,
+        hint: "Pause",
+        description: "Pause the match",
+    },
+    Binding {
+        keys: &[key(KeyCode::Char('p')), key(KeyCode::Esc)],
+        screens: &[Screen::Paused],
+        action: Action::Resume,
+        hint: "Resume",
+        description: "Resume the match",
+    },
+    Binding {
+        keys: &[key(KeyCode::Char('h'))],
+        screens: &[Screen::Playing],
+        action: Action::Help,
+        hint: "Help",
+        description: "Pause and show this help",
+    },
+    Binding {
+        keys: &[key(KeyCode::Char('m'))],
+        screens: MATCH,
+        action: Action::Mute,
+        hint: "Mute",
+        description: "Mute or unmute the sound",
+    },
+    Binding {
+        keys: &[key(KeyCode::Esc)],
+        screens: &[Screen::CustomDifficulty],
+        action: Action::Back,
+        hint: "Back",
+        description: "Save the difficulty and go back to the match setup",
+    },
+    Binding {
+        keys: &[key(KeyCode::Esc)],
+        screens: &[Screen::Settings, Screen::Setup, Screen::GameOver],
+        action: Action::Back,
+        hint: "Back",
+        description: "Go back to the main menu",
+    },
+    Binding {
+        keys: &[key(KeyCode::Char('q')), key(KeyCode::Esc)],
+        screens: &[Screen::MainMenu],
+        action: Action::Quit,
+        hint: "Quit",
+        description: "Quit the game",
+    },
+    Binding {
+        keys: &[key(KeyCode::Char('q'))],
+        screens: &[
+            Screen::Settings,
+            Screen::Setup,
+            Screen::CustomDifficulty,
+            Screen::Playing,
+            Screen::Paused,
+            Screen::GameOver,
+            Screen::Help,
+        ],
+        action: Action::Quit,
+        hint: "Quit",
+        description: "Quit the game, asking first during a match",
+    },
+    Binding {
+        keys: &[ctrl(KeyCode::Char('c'))],
+        screens: ALL,
+        action: Action::ForceQuit,
+        hint: "",
+        description: "Quit right away",
+    },
+];
+
+/// Returns the action `event` triggers on `screen`.
+pub fn action(event: KeyEvent, screen: Screen) -> Option<Action> {
+    BINDINGS
+        .iter()
+        .find(|binding| {
+            binding.screens.contains(&screen) && binding.keys.iter().any(|key| key.matches(event))
+        })
+        .map(|binding| binding.action)
+}
+
+/// Returns the bindings that do something on `screen` right now.
+pub fn bindings(app: &App, screen: Screen) -> impl Iterator<Item = &'static Binding> + '_ {
+    BINDINGS.iter().filter(move |binding| {
+        binding.screens.contains(&screen) && binding.action.is_available(app)
+    })
+}
+
+/// Name of `key` for hints, arrow keys named by `glyphs`.
+pub fn key_name(key: Key, glyphs: &Glyphs) -> String {
+    let name = match key.code {
+        KeyCode::Char(c) => c.to_uppercase().to_string(),
+        KeyCode::Up => glyphs.arrow_keys[0].to_string(),
+        KeyCode::Down => glyphs.arrow_keys[1].to_string(),
+        KeyCode::Left => glyphs.arrow_keys[2].to_string(),
+        KeyCode::Right => glyphs.arrow_keys[3].to_string(),
+        KeyCode::PageUp => "PgUp".to_string(),
+        KeyCode::PageDown => "PgDn".to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Backspace => "Backspace".to_string(),
+        KeyCode::Delete => "Del".to_string(),
+        KeyCode::Home => "Home".to_string(),
+        KeyCode::End => "End".to_string(),
+        _ => "?".to_string(),
+    };
+    if key.ctrl {
+        format!("Ctrl+{name}")
+    } else {
+        name
+    }
+}
+
+pub fn key_names(keys: &[Key], glyphs: &Glyphs) -> String {
+    keys.iter()
+        .map(|key| key_name(*key, glyphs))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Returns the one line summary of the keys for `screen`, like `↑/↓ Move  Enter Select`.
+pub fn footer(app: &App, screen: Screen) -> String {
+    let mut hints: Vec<(String, &str)> = vec![];
+    for binding in bindings(app, screen).filter(|binding| !binding.hint.is_empty()) {
+        let keys = key_names(binding.keys, app.settings.glyphs());
+        match hints.iter_mut().find(|(_, hint)| *hint == binding.hint) {
+            Some((names, _)) => {
+                names.push('/');
+                names.push_str(&keys);
+            }
+            None => hints.push((keys, binding.hint)),
+        }
+    }
+    hints
+        .iter()
+        .map(|(keys, hint)| format!("{keys} {hint}"))
+        .collect::<Vec<_>>()
+        .join("  ")
+}
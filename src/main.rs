@@ -4,22 +4,106 @@ mod tui;
 mod drawer;
 mod font;
 mod app;
+mod rules;
+mod effects;
+mod settings;
+mod theme;
+mod audio;
+mod arena;
+mod controller;
+mod simulate;
+mod keymap;
+mod save;
+mod difficulty;
+mod menu;
+mod text_input;
+mod announce;
 
 use std::io::{stdout, Result};
+use std::path::PathBuf;
 use ratatui::prelude::*;
 use tui::Tui;
 use app::App;
+use audio::Audio;
+use announce::Announcer;
+use arena::Arena;
+use settings::config_dir;
+use theme::ColorDepth;
 
 fn main() -> Result<()> {
+    if std::env::args().nth(1).as_deref() == Some("simulate") {
+        let args: Vec<String> = std::env::args().skip(2).collect();
+        if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+            println!("{}", simulate::USAGE);
+            return Ok(());
+        }
+        if let Err(err) = simulate::run(args.into_iter()) {
+            eprintln!("{err}\n\n{}", simulate::USAGE);
+            std::process::exit(2);
+        }
+        return Ok(());
+    }
+
+    let wav_path = std::env::args()
+        .skip_while(|arg| arg != "--wav")
+        .nth(1)
+        .map(PathBuf::from);
+
+    let bot_command = std::env::args().skip_while(|arg| arg != "--bot").nth(1);
+
+    let announce_path = std::env::args()
+        .skip_while(|arg| arg != "--announce")
+        .nth(1)
+        .map(PathBuf::from);
+
+    let mut app = App::new();
+    app.bot_command = bot_command;
+    app.audio = Audio::new(audio::default_backend(wav_path.as_deref())?);
+    if let Some(path) = announce_path {
+        app.announcer = Announcer::open(&path)?;
+    }
+    app.settings.text_mode = std::env::args().any(|arg| arg == "--text");
+    app.settings.ascii = if std::env::args().any(|arg| arg == "--ascii") {
+        true
+    } else if std::env::args().any(|arg| arg == "--unicode") {
+        false
+    } else {
+        !settings::unicode_supported()
+    };
+    let colors = std::env::args().skip_while(|arg| arg != "--colors").nth(1);
+    app.settings.color_depth = match colors {
+        Some(depth) => ColorDepth::parse(&depth).unwrap_or_else(|err| {
+            eprintln!("{err}");
+            std::process::exit(2);
+        }),
+        None => ColorDepth::detect(),
+    };
+
+    let (arenas, errors) = Arena::load_all(config_dir().map(|dir| dir.join("arenas")).as_deref());
+    for error in errors {
+        eprintln!("skipping arena {error}");
+    }
+    app.arenas = arenas;
+
+    match difficulty::load_custom() {
+        Ok(custom) => app.custom_difficulty = custom.unwrap_or_default(),
+        Err(err) => eprintln!("ignoring the custom difficulty: {err}"),
+    }
+
+    match save::load() {
+        Ok(saved) => app.saved = saved,
+        Err(err) => app.notice = Some(format!("Ignoring the saved match: {err}")),
+    }
+    app.show_main_menu();
+
     let terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
 
     let mut tui = Tui::new(terminal);
     tui.init()?;
-    let mut app = App::new();
 
     let result = tui.run(&mut app);
 
     tui.exit()?;
     result?;
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,703 @@
+use crate::app::App;
+use crate::controller::{self, Player};
+use crate::difficulty::*;
+use crate::font::FontStyle;
+use crate::game::{GameMode, Side, MAX_TRAIL_LENGTH};
+use crate::rules::*;
+use crate::settings::{MAX_GAME_SPEED, MAX_VOLUME, MIN_GAME_SPEED};
+use crate::text_input::{Edit, TextInput};
+use crate::theme::Theme;
+
+/// Characters a player name can have, so it fits next to the paddle.
+pub const MAX_NAME_LENGTH: usize = 12;
+
+/// What a menu item edits and how it takes the keys. Items read and write the app through plain
+/// functions, so whole menus can be declared as constants.
+pub enum ItemKind {
+    /// One of `count` options, picked with left and right.
+    Choice {
+        get: fn(&App) -> i8,
+        set: fn(&mut App, i8),
+        count: fn(&App) -> i8,
+        name: fn(&App, i8) -> String,
+    },
+    /// A number from `min` to `max`, stepped with left and right.
+    Range {
+        get: fn(&App) -> i32,
+        set: fn(&mut App, i32),
+        min: i32,
+        max: i32,
+        format: fn(&App, i32) -> String,
+    },
+    /// On or off, flipped with left, right or Enter.
+    Toggle {
+        get: fn(&App) -> bool,
+        set: fn(&mut App, bool),
+    },
+    /// Free text, typed after pressing Enter and only kept when `validate` accepts it.
+    Text {
+        get: fn(&App) -> String,
+        set: fn(&mut App, String),
+        max_len: usize,
+        validate: fn(&str) -> Result<(), String>,
+        /// Shown while the text is empty.
+        placeholder: &'static str,
+    },
+    /// Runs `press` on Enter.
+    Button { press: fn(&mut App) },
+    /// Titles the items after it, never selected.
+    Heading,
+}
+
+pub struct Item {
+    pub label: &'static str,
+    pub kind: ItemKind,
+    /// Whether the item is in the menu right now, hidden items are skipped over.
+    pub shown: fn(&App) -> bool,
+}
+
+fn always(_app: &App) -> bool {
+    true
+}
+
+const fn choice(
+    label: &'static str,
+    get: fn(&App) -> i8,
+    set: fn(&mut App, i8),
+    count: fn(&App) -> i8,
+    name: fn(&App, i8) -> String,
+) -> Item {
+    Item {
+        label,
+        kind: ItemKind::Choice {
+            get,
+            set,
+            count,
+            name,
+        },
+        shown: always,
+    }
+}
+
+const fn range(
+    label: &'static str,
+    get: fn(&App) -> i32,
+    set: fn(&mut App, i32),
+    (min, max): (i32, i32),
+    format: fn(&App, i32) -> String,
+) -> Item {
+    Item {
+        label,
+        kind: ItemKind::Range {
+            get,
+            set,
+            min,
+            max,
+            format,
+        },
+        shown: always,
+    }
+}
+
+const fn toggle(label: &'static str, get: fn(&App) -> bool, set: fn(&mut App, bool)) -> Item {
+    Item {
+        label,
+        kind: ItemKind::Toggle { get, set },
+        shown: always,
+    }
+}
+
+const fn text(
+    label: &'static str,
+    get: fn(&App) -> String,
+    set: fn(&mut App, String),
+    max_len: usize,
+    validate: fn(&str) -> Result<(), String>,
+    placeholder: &'static str,
+) -> Item {
+    Item {
+        label,
+        kind: ItemKind::Text {
+            get,
+            set,
+            max_len,
+            validate,
+            placeholder,
+        },
+        shown: always,
+    }
+}
+
+const fn button(label: &'static str, press: fn(&mut App)) -> Item {
+    Item {
+        label,
+        kind: ItemKind::Button { press },
+        shown: always,
+    }
+}
+
+const fn heading(label: &'static str) -> Item {
+    Item {
+        label,
+        kind: ItemKind::Heading,
+        shown: always,
+    }
+}
+
+impl Item {
+    const fn shown_if(self, shown: fn(&App) -> bool) -> Self {
+        Self { shown, ..self }
+    }
+
+    /// Text of the value, `None` for buttons and headings.
+    pub fn value(&self, app: &App) -> Option<String> {
+        match self.kind {
+            ItemKind::Choice { get, name, .. } => Some(name(app, get(app))),
+            ItemKind::Range { get, format, .. } => Some(format(app, get(app))),
+            ItemKind::Toggle { get, .. } => Some(if get(app) { "On" } else { "Off" }.to_string()),
+            ItemKind::Text {
+                get, placeholder, ..
+            } => Some(match get(app) {
+                text if text.is_empty() => placeholder.to_string(),
+                text => text,
+            }),
+            ItemKind::Button { .. } | ItemKind::Heading => None,
+        }
+    }
+
+    /// Widest the value gets, so the arrows around it stay put while it changes.
+    pub fn value_width(&self, app: &App) -> usize {
+        let width = |text: String| text.chars().count();
+        match self.kind {
+            ItemKind::Choice { count, name, .. } => (0..count(app))
+                .map(|i| width(name(app, i)))
+                .max()
+                .unwrap_or(0),
+            ItemKind::Range {
+                min, max, format, ..
+            } => width(format(app, min)).max(width(format(app, max))),
+            ItemKind::Toggle { .. } => 3,
+            ItemKind::Text { .. } | ItemKind::Button { .. } | ItemKind::Heading => 0,
+        }
+    }
+
+    /// Whether left and right can still move the value down and up.
+    pub fn bounds(&self, app: &App) -> (bool, bool) {
+        match self.kind {
+            ItemKind::Choice { get, count, .. } => (get(app) > 0, get(app) < count(app) - 1),
+            ItemKind::Range { get, min, max, .. } => (get(app) > min, get(app) < max),
+            ItemKind::Toggle { .. } => (true, true),
+            ItemKind::Text { .. } | ItemKind::Button { .. } | ItemKind::Heading => (false, false),
+        }
+    }
+}
+
+/// Where the selection of a menu is.
+#[derive(Default)]
+pub struct MenuState {
+    /// Index of the selected item in `Menu::items`.
+    pub selected: usize,
+    /// Rows scrolled past at the top when the menu doesn't fit the screen.
+    pub scroll: usize,
+    /// What is typed into the selected text item, `None` while not typing.
+    pub editing: Option<TextInput>,
+}
+
+pub struct Menu {
+    pub items: &'static [Item],
+    pub state: fn(&App) -> &MenuState,
+    pub state_mut: fn(&mut App) -> &mut MenuState,
+}
+
+impl Menu {
+    /// Indices of the items shown right now.
+    pub fn shown(&self, app: &App) -> Vec<usize> {
+        (0..self.items.len())
+            .filter(|&i| (self.items[i].shown)(app))
+            .collect()
+    }
+
+    /// Indices of the shown items that can be selected, which headings can't.
+    fn selectable(&self, app: &App) -> Vec<usize> {
+        self.shown(app)
+            .into_iter()
+            .filter(|&i| !matches!(self.items[i].kind, ItemKind::Heading))
+            .collect()
+    }
+
+    /// Index of the item labelled `label`, to open the menu with it selected.
+    pub fn position(&self, label: &str) -> usize {
+        self.items
+            .iter()
+            .position(|item| item.label == label)
+            .unwrap_or(0)
+    }
+
+    /// The selected item, moved on to the next shown one when it has been hidden.
+    pub fn selected(&self, app: &App) -> usize {
+        let selected = (self.state)(app).selected;
+        let shown = self.selectable(app);
+        shown
+            .iter()
+            .find(|&&i| i >= selected)
+            .or(shown.last())
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn is_editing(&self, app: &App) -> bool {
+        (self.state)(app).editing.is_some()
+    }
+
+    fn step_selection(&self, app: &mut App, forward: bool) {
+        let shown = self.selectable(app);
+        let Some(position) = shown.iter().position(|&i| i == self.selected(app)) else {
+            return;
+        };
+        let position = if forward {
+            (position + 1) % shown.len()
+        } else {
+            (position + shown.len() - 1) % shown.len()
+        };
+        (self.state_mut)(app).selected = shown[position];
+    }
+
+    pub fn select_prev(&self, app: &mut App) {
+        self.step_selection(app, false);
+    }
+
+    pub fn select_next(&self, app: &mut App) {
+        self.step_selection(app, true);
+    }
+
+    /// Moves the value of the selected item `delta` steps, it stops at either end.
+    pub fn change(&self, app: &mut App, delta: i32) {
+        match self.items[self.selected(app)].kind {
+            ItemKind::Choice {
+                get, set, count, ..
+            } => {
+                let value = (get(app) as i32 + delta).clamp(0, count(app) as i32 - 1);
+                set(app, value as i8);
+            }
+            ItemKind::Range {
+                get, set, min, max, ..
+            } => set(app, (get(app) + delta).clamp(min, max)),
+            ItemKind::Toggle { get, set } => set(app, !get(app)),
+            ItemKind::Text { .. } | ItemKind::Button { .. } | ItemKind::Heading => (),
+        }
+    }
+
+    /// Presses buttons, flips toggles and starts typing into text items.
+    pub fn press(&self, app: &mut App) {
+        match self.items[self.selected(app)].kind {
+            ItemKind::Button { press } => press(app),
+            ItemKind::Toggle { get, set } => set(app, !get(app)),
+            ItemKind::Text { get, max_len, .. } => {
+                (self.state_mut)(app).editing = Some(TextInput::new(&get(app), max_len))
+            }
+            ItemKind::Choice { .. } | ItemKind::Range { .. } | ItemKind::Heading => (),
+        }
+    }
+
+    pub fn type_char(&self, app: &mut App, c: char) {
+        if let Some(input) = &mut (self.state_mut)(app).editing {
+            input.insert(c);
+        }
+    }
+
+    pub fn paste(&self, app: &mut App, text: &str) {
+        if let Some(input) = &mut (self.state_mut)(app).editing {
+            input.paste(text);
+        }
+    }
+
+    pub fn edit(&self, app: &mut App, edit: Edit) {
+        if let Some(input) = &mut (self.state_mut)(app).editing {
+            input.edit(edit);
+        }
+    }
+
+    /// Keeps the typed text if it is valid, otherwise goes on typing with the error as a notice.
+    pub fn finish_editing(&self, app: &mut App) {
+        let ItemKind::Text { set, validate, .. } = self.items[self.selected(app)].kind else {
+            return;
+        };
+        let Some(input) = (self.state_mut)(app).editing.take() else {
+            return;
+        };
+        match validate(&input.text()) {
+            Ok(()) => set(app, input.text()),
+            Err(err) => {
+                app.notice = Some(err);
+                (self.state_mut)(app).editing = Some(input);
+            }
+        }
+    }
+
+    pub fn cancel_editing(&self, app: &mut App) {
+        (self.state_mut)(app).editing = None;
+    }
+}
+
+/// Returns the menu the app is showing, if it is one of these.
+pub fn shown(app: &App) -> Option<&'static Menu> {
+    if app.game_started {
+        None
+    } else if app.settings_menu_shown {
+        Some(&SETTINGS)
+    } else if app.custom_menu_shown {
+        Some(&CUSTOM_DIFFICULTY)
+    } else if app.difficulty_menu_shown {
+        Some(&SETUP)
+    } else {
+        None
+    }
+}
+
+fn name_of<T: TryFrom<i8> + ToString>(value: i8) -> String {
+    T::try_from(value)
+        .map(|value| value.to_string())
+        .unwrap_or_default()
+}
+
+fn handicap_size(_app: &App, length: i32) -> String {
+    match length {
+        0 => "Normal".to_string(),
+        length => format!("{length:+}"),
+    }
+}
+
+fn handicap_bonus(app: &App, bonus: i32) -> String {
+    match bonus {
+        0 => "None".to_string(),
+        bonus if app.has_lives() => format!("+{bonus} lives"),
+        bonus => format!("+{bonus} points"),
+    }
+}
+
+fn times(_app: &App, value: i32) -> String {
+    format!("{value}x")
+}
+
+fn per_second(_app: &App, value: i32) -> String {
+    format!("{value}/s")
+}
+
+fn any_text(_text: &str) -> Result<(), String> {
+    Ok(())
+}
+
+/// Drops players that are gone from the list, like the bot once its command is cleared.
+fn clamp_players(app: &mut App) {
+    app.left_player = app.left_player.min(app.player_count() - 1);
+    app.right_player = app.right_player.min(app.player_count() - 1);
+}
+
+pub const SETTINGS: Menu = Menu {
+    items: &[
+        toggle(
+            "Reduced Motion:",
+            |app| app.settings.reduced_motion,
+            |app, on| {
+                app.settings.reduced_motion = on;
+                app.effects.clear();
+            },
+        ),
+        range(
+            "Ball Trail:",
+            |app| app.settings.ball_trail as i32,
+            |app, length| app.settings.ball_trail = length as u8,
+            (0, MAX_TRAIL_LENGTH as i32),
+            |_, length| match length {
+                0 => "Off".to_string(),
+                length => length.to_string(),
+            },
+        ),
+        toggle(
+            "Sound:",
+            |app| !app.settings.muted,
+            |app, on| app.settings.muted = !on,
+        ),
+        range(
+            "Volume:",
+            |app| app.settings.volume as i32,
+            |app, volume| app.settings.volume = volume as u8,
+            (0, MAX_VOLUME as i32),
+            |_, volume| volume.to_string(),
+        ),
+        choice(
+            "Score Font:",
+            |app| app.settings.font_style as i8,
+            |app, style| {
+                app.settings.font_style = FontStyle::try_from(style).unwrap_or(FontStyle::Auto)
+            },
+            |_| FontStyle::len() as i8,
+            |_, style| name_of::<FontStyle>(style),
+        ),
+        heading("Accessibility"),
+        choice(
+            "Theme:",
+            |app| app.settings.theme as i8,
+            |app, theme| app.settings.theme = Theme::try_from(theme).unwrap_or(Theme::Classic),
+            |_| Theme::len() as i8,
+            |_, theme| name_of::<Theme>(theme),
+        ),
+        toggle(
+            "Blinking:",
+            |app| app.settings.blinking,
+            |app, on| app.settings.blinking = on,
+        ),
+        toggle(
+            "Big Ball & Paddles:",
+            |app| app.settings.large_glyphs,
+            |app, on| app.settings.large_glyphs = on,
+        ),
+        toggle(
+            "Text Mode:",
+            |app| app.settings.text_mode,
+            |app, on| app.settings.text_mode = on,
+        ),
+        range(
+            "Game Speed:",
+            |app| app.settings.game_speed as i32,
+            |app, speed| app.settings.game_speed = speed as u8,
+            (MIN_GAME_SPEED as i32, MAX_GAME_SPEED as i32),
+            |_, speed| match speed as u8 {
+                MAX_GAME_SPEED => "Full".to_string(),
+                speed => format!("{speed}0%"),
+            },
+        ),
+        button("Back", App::show_main_menu),
+    ],
+    state: |app| &app.settings_menu,
+    state_mut: |app| &mut app.settings_menu,
+};
+
+pub const SETUP: Menu = Menu {
+    items: &[
+        choice(
+            "Select Mode:",
+            |app| app.game_mode,
+            |app, mode| app.game_mode = mode,
+            |_| GameMode::len() as i8,
+            |_, mode| name_of::<GameMode>(mode),
+        ),
+        choice(
+            "Select Difficulty:",
+            |app| app.difficulty,
+            |app, difficulty| app.difficulty = difficulty,
+            |_| Difficulty::len() as i8,
+            |_, difficulty| name_of::<Difficulty>(difficulty),
+        ),
+        button("Edit Custom Difficulty", App::show_custom_menu)
+            .shown_if(|app| app.difficulty == Difficulty::Custom as i8),
+        choice(
+            "Select Scoring Rule:",
+            |app| app.scoring_rule,
+            |app, rule| app.scoring_rule = rule,
+            |_| ScoringRule::len() as i8,
+            |_, rule| name_of::<ScoringRule>(rule),
+        ),
+        range(
+            "Select Win Score:",
+            |app| app.win_score as i32,
+            |app, score| app.win_score = score as u16,
+            (1, MAX_WIN_SCORE as i32),
+            |_, score| score.to_string(),
+        )
+        .shown_if(|app| !app.has_lives() && !app.is_timed_match()),
+        range(
+            "Select Lives:",
+            |app| app.win_score as i32,
+            |app, lives| app.win_score = lives as u16,
+            (1, MAX_WIN_SCORE as i32),
+            |_, lives| lives.to_string(),
+        )
+        .shown_if(App::has_lives),
+        range(
+            "Select Time Limit:",
+            |app| app.time_limit_minutes as i32,
+            |app, minutes| app.time_limit_minutes = minutes as u16,
+            (1, MAX_TIME_LIMIT_MINUTES as i32),
+            |_, minutes| format!("{minutes} min"),
+        )
+        .shown_if(App::is_timed_match),
+        choice(
+            "Select Server:",
+            |app| app.server_rule,
+            |app, rule| app.server_rule = rule,
+            |_| ServerRule::len() as i8,
+            |_, rule| name_of::<ServerRule>(rule),
+        ),
+        choice(
+            "Select Serve:",
+            |app| app.serve_control,
+            |app, control| app.serve_control = control,
+            |_| ServeControl::len() as i8,
+            |_, control| name_of::<ServeControl>(control),
+        ),
+        choice(
+            "Select Arena:",
+            |app| app.arena,
+            |app, arena| app.arena = arena,
+            |app| app.arenas.len() as i8,
+            |app, arena| app.arenas[arena as usize].name.clone(),
+        ),
+        text(
+            "Seed:",
+            |app| app.seed.map(|seed| seed.to_string()).unwrap_or_default(),
+            |app, seed| app.seed = seed.trim().parse().ok(),
+            20,
+            |seed| match seed.trim() {
+                "" => Ok(()),
+                seed if seed.parse::<u64>().is_ok() => Ok(()),
+                seed => Err(format!(
+                    "`{seed}` is not a seed, use a whole number or leave it empty"
+                )),
+            },
+            "Random",
+        ),
+        choice(
+            "Left Player:",
+            |app| app.left_player,
+            |app, player| app.left_player = player,
+            App::player_count,
+            |_, player| name_of::<Player>(player),
+        ),
+        text(
+            "Left Name:",
+            |app| app.names[Side::Left.index()].clone(),
+            |app, name| app.names[Side::Left.index()] = name,
+            MAX_NAME_LENGTH,
+            any_text,
+            "None",
+        ),
+        range(
+            "Left Paddle Size:",
+            |app| app.handicaps[Side::Left.index()].length as i32,
+            |app, length| app.handicaps[Side::Left.index()].length = length as i8,
+            (MIN_HANDICAP_LENGTH as i32, MAX_HANDICAP_LENGTH as i32),
+            handicap_size,
+        ),
+        range(
+            "Left Paddle Speed:",
+            |app| app.handicaps[Side::Left.index()].speed as i32,
+            |app, speed| app.handicaps[Side::Left.index()].speed = speed as u8,
+            (1, MAX_HANDICAP_SPEED as i32),
+            times,
+        ),
+        range(
+            "Left Head Start:",
+            |app| app.handicaps[Side::Left.index()].bonus as i32,
+            |app, bonus| app.handicaps[Side::Left.index()].bonus = bonus as u16,
+            (0, MAX_HANDICAP_BONUS as i32),
+            handicap_bonus,
+        ),
+        choice(
+            "Right Player:",
+            |app| app.right_player,
+            |app, player| app.right_player = player,
+            App::player_count,
+            |_, player| name_of::<Player>(player),
+        ),
+        text(
+            "Right Name:",
+            |app| app.names[Side::Right.index()].clone(),
+            |app, name| app.names[Side::Right.index()] = name,
+            MAX_NAME_LENGTH,
+            any_text,
+            "None",
+        ),
+        range(
+            "Right Paddle Size:",
+            |app| app.handicaps[Side::Right.index()].length as i32,
+            |app, length| app.handicaps[Side::Right.index()].length = length as i8,
+            (MIN_HANDICAP_LENGTH as i32, MAX_HANDICAP_LENGTH as i32),
+            handicap_size,
+        ),
+        range(
+            "Right Paddle Speed:",
+            |app| app.handicaps[Side::Right.index()].speed as i32,
+            |app, speed| app.handicaps[Side::Right.index()].speed = speed as u8,
+            (1, MAX_HANDICAP_SPEED as i32),
+            times,
+        ),
+        range(
+            "Right Head Start:",
+            |app| app.handicaps[Side::Right.index()].bonus as i32,
+            |app, bonus| app.handicaps[Side::Right.index()].bonus = bonus as u16,
+            (0, MAX_HANDICAP_BONUS as i32),
+            handicap_bonus,
+        ),
+        text(
+            "Bot Command:",
+            |app| app.bot_command.clone().unwrap_or_default(),
+            |app, command| {
+                let command = command.trim().to_string();
+                app.bot_command = (!command.is_empty()).then_some(command);
+                clamp_players(app);
+            },
+            200,
+            controller::check_command,
+            "None",
+        ),
+        button("Start Game", App::start_game),
+        button("Back", App::show_main_menu),
+    ],
+    state: |app| &app.setup_menu,
+    state_mut: |app| &mut app.setup_menu,
+};
+
+pub const CUSTOM_DIFFICULTY: Menu = Menu {
+    items: &[
+        range(
+            "Ball Speed:",
+            |app| app.custom_difficulty.ball_speed as i32,
+            |app, speed| app.custom_difficulty.ball_speed = speed as u16,
+            (MIN_BALL_SPEED as i32, MAX_BALL_SPEED as i32),
+            per_second,
+        ),
+        range(
+            "Acceleration:",
+            |app| app.custom_difficulty.acceleration as i32,
+            |app, acceleration| app.custom_difficulty.acceleration = acceleration as u16,
+            (0, MAX_ACCELERATION as i32),
+            |_, acceleration| match acceleration {
+                0 => "Off".to_string(),
+                acceleration => format!("+{acceleration}/hit"),
+            },
+        ),
+        range(
+            "Top Speed:",
+            |app| app.custom_difficulty.max_speed as i32,
+            |app, speed| app.custom_difficulty.max_speed = speed as u16,
+            (MIN_BALL_SPEED as i32, MAX_BALL_SPEED as i32),
+            // The ball never goes slower than it is served.
+            |app, speed| per_second(app, speed.max(app.custom_difficulty.ball_speed as i32)),
+        ),
+        range(
+            "Paddle Length:",
+            |app| app.custom_difficulty.paddle_length as i32,
+            |app, length| app.custom_difficulty.paddle_length = length as u16,
+            (MIN_PADDLE_LENGTH as i32, MAX_PADDLE_LENGTH as i32),
+            |_, length| length.to_string(),
+        ),
+        range(
+            "Paddle Speed:",
+            |app| app.custom_difficulty.paddle_speed as i32,
+            |app, speed| app.custom_difficulty.paddle_speed = speed as u16,
+            (1, MAX_PADDLE_SPEED as i32),
+            times,
+        ),
+        range(
+            "CPU Skill:",
+            |app| app.custom_difficulty.ai_skill as i32,
+            |app, skill| app.custom_difficulty.ai_skill = skill as u8,
+            (0, MAX_AI_SKILL as i32),
+            |_, skill| format!("{skill}/{MAX_AI_SKILL}"),
+        ),
+        button("Back", App::hide_custom_menu),
+    ],
+    state: |app| &app.custom_menu,
+    state_mut: |app| &mut app.custom_menu,
+};
@@ -0,0 +1,410 @@
+use crate::game::Side;
+use std::fmt::Display;
+use std::time::Duration;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ScoringRule {
+    FirstTo,
+    WinByTwo,
+    Timed,
+}
+
+impl Display for ScoringRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::FirstTo => write!(f, "First To"),
+            Self::WinByTwo => write!(f, "Win By 2"),
+            Self::Timed => write!(f, " Timed  "),
+        }
+    }
+}
+
+impl TryFrom<i8> for ScoringRule {
+    type Error = ();
+    fn try_from(value: i8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::FirstTo),
+            1 => Ok(Self::WinByTwo),
+            2 => Ok(Self::Timed),
+            _ => Err(()),
+        }
+    }
+}
+
+impl ScoringRule {
+    pub fn len() -> u8 {
+        3
+    }
+
+    /// One line about how a match is won, for the help window.
+    pub fn description(&self) -> &'static str {
+        match *self {
+            Self::FirstTo => "The first to the win score wins.",
+            Self::WinByTwo => "Reach the win score with a two point lead.",
+            Self::Timed => "The most points when time runs out, ties go to a golden point.",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ServerRule {
+    Random,
+    Loser,
+    Fixed(Side),
+    Rotation(u16),
+}
+
+impl Display for ServerRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Random => write!(f, " Random "),
+            Self::Loser => write!(f, " Loser  "),
+            Self::Fixed(side) => write!(f, "{: ^8}", side.to_string()),
+            Self::Rotation(points) => write!(f, "Every {: <2}", points),
+        }
+    }
+}
+
+impl TryFrom<i8> for ServerRule {
+    type Error = ();
+    fn try_from(value: i8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Random),
+            1 => Ok(Self::Loser),
+            2 => Ok(Self::Fixed(Side::Left)),
+            3 => Ok(Self::Fixed(Side::Right)),
+            4.. if value < Self::len() as i8 => Ok(Self::Rotation(value as u16 - 3)),
+            _ => Err(()),
+        }
+    }
+}
+
+impl ServerRule {
+    pub fn len() -> u8 {
+        4 + MAX_SERVE_ROTATION as u8
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ServeControl {
+    Countdown,
+    KeyPress,
+}
+
+impl Display for ServeControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Countdown => write!(f, "Countdown"),
+            Self::KeyPress => write!(f, "Key Press"),
+        }
+    }
+}
+
+impl TryFrom<i8> for ServeControl {
+    type Error = ();
+    fn try_from(value: i8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Countdown),
+            1 => Ok(Self::KeyPress),
+            _ => Err(()),
+        }
+    }
+}
+
+impl ServeControl {
+    pub fn len() -> u8 {
+        2
+    }
+}
+
+pub const SERVE_COUNTDOWN: Duration = Duration::from_secs(3);
+
+pub const MAX_WIN_SCORE: u16 = 100;
+pub const MAX_TIME_LIMIT_MINUTES: u16 = 30;
+pub const MAX_SERVE_ROTATION: u16 = 10;
+
+pub const MIN_HANDICAP_LENGTH: i8 = -2;
+pub const MAX_HANDICAP_LENGTH: i8 = 6;
+pub const MAX_HANDICAP_SPEED: u8 = 3;
+pub const MAX_HANDICAP_BONUS: u16 = 9;
+
+/// Evens out a match between players of different skill, picked for each side in the setup menu.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Handicap {
+    /// Cells added to the paddle, or taken off it when negative.
+    pub length: i8,
+    /// Cells the paddle slides per move.
+    pub speed: u8,
+    /// Points the side starts with, or extra lives in modes played with lives.
+    pub bonus: u16,
+}
+
+impl Default for Handicap {
+    fn default() -> Self {
+        Self {
+            length: 0,
+            speed: 1,
+            bonus: 0,
+        }
+    }
+}
+
+/// How a match is won and who serves.
+///
+/// `win_score` is ignored by `ScoringRule::Timed` and `time_limit` is ignored by the other rules.
+#[derive(Clone, Copy)]
+pub struct MatchRules {
+    pub scoring: ScoringRule,
+    pub win_score: u16,
+    pub time_limit: Duration,
+    pub server: ServerRule,
+    pub serve_control: ServeControl,
+    /// Handicaps by side, in the order of `Side::ALL`.
+    pub handicaps: [Handicap; 4],
+}
+
+impl Default for MatchRules {
+    fn default() -> Self {
+        Self {
+            scoring: ScoringRule::FirstTo,
+            win_score: 10,
+            time_limit: Duration::from_secs(3 * 60),
+            server: ServerRule::Random,
+            serve_control: ServeControl::Countdown,
+            handicaps: [Handicap::default(); 4],
+        }
+    }
+}
+
+impl MatchRules {
+    pub fn handicap(&self, side: Side) -> Handicap {
+        self.handicaps[side.index()]
+    }
+
+    pub fn winner(&self, l_score: u16, r_score: u16, elapsed: Duration) -> Option<Side> {
+        let leader = match l_score.cmp(&r_score) {
+            std::cmp::Ordering::Greater => Side::Left,
+            std::cmp::Ordering::Less => Side::Right,
+            std::cmp::Ordering::Equal => return None,
+        };
+
+        let won = match self.scoring {
+            ScoringRule::FirstTo => l_score.max(r_score) >= self.win_score,
+            ScoringRule::WinByTwo => {
+                l_score.max(r_score) >= self.win_score && l_score.abs_diff(r_score) >= 2
+            }
+            ScoringRule::Timed => elapsed >= self.time_limit,
+        };
+
+        if won {
+            Some(leader)
+        } else {
+            None
+        }
+    }
+
+    /// Both players are level at or past game point, so the next point only gives the advantage.
+    pub fn is_deuce(&self, l_score: u16, r_score: u16) -> bool {
+        self.scoring == ScoringRule::WinByTwo && l_score == r_score && l_score + 1 >= self.win_score
+    }
+
+    pub fn advantage(&self, l_score: u16, r_score: u16) -> Option<Side> {
+        if self.scoring != ScoringRule::WinByTwo
+            || l_score.min(r_score) + 1 < self.win_score
+            || l_score.abs_diff(r_score) != 1
+        {
+            return None;
+        }
+
+        if l_score > r_score {
+            Some(Side::Left)
+        } else {
+            Some(Side::Right)
+        }
+    }
+
+    /// The clock has run out on a tied timed match, the next point wins.
+    pub fn is_sudden_death(&self, l_score: u16, r_score: u16, elapsed: Duration) -> bool {
+        self.scoring == ScoringRule::Timed && elapsed >= self.time_limit && l_score == r_score
+    }
+
+    /// Returns who serves the next point, or `None` when the server is picked at random.
+    pub fn server(
+        &self,
+        first_server: Side,
+        points_played: u16,
+        last_point_loser: Option<Side>,
+    ) -> Option<Side> {
+        match self.server {
+            ServerRule::Random => None,
+            ServerRule::Loser => Some(last_point_loser.unwrap_or(first_server)),
+            ServerRule::Fixed(side) => Some(side),
+            ServerRule::Rotation(points) => {
+                if (points_played / points).is_multiple_of(2) {
+                    Some(first_server)
+                } else {
+                    Some(first_server.opponent())
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const L: Option<Side> = Some(Side::Left);
+    const R: Option<Side> = Some(Side::Right);
+
+    fn rules(scoring: ScoringRule) -> MatchRules {
+        MatchRules {
+            scoring,
+            win_score: 11,
+            time_limit: Duration::from_secs(60),
+            ..MatchRules::default()
+        }
+    }
+
+    #[test]
+    fn winner() {
+        let table = [
+            (ScoringRule::FirstTo, 0, 0, 0, None),
+            (ScoringRule::FirstTo, 10, 9, 0, None),
+            (ScoringRule::FirstTo, 11, 9, 0, L),
+            (ScoringRule::FirstTo, 11, 10, 0, L),
+            (ScoringRule::FirstTo, 9, 11, 0, R),
+            (ScoringRule::FirstTo, 10, 10, 600, None),
+            (ScoringRule::WinByTwo, 10, 8, 0, None),
+            (ScoringRule::WinByTwo, 11, 10, 0, None),
+            (ScoringRule::WinByTwo, 11, 9, 0, L),
+            (ScoringRule::WinByTwo, 12, 10, 0, L),
+            (ScoringRule::WinByTwo, 13, 14, 0, None),
+            (ScoringRule::WinByTwo, 13, 15, 0, R),
+            (ScoringRule::Timed, 20, 3, 59, None),
+            (ScoringRule::Timed, 5, 3, 60, L),
+            (ScoringRule::Timed, 2, 4, 61, R),
+            (ScoringRule::Timed, 3, 3, 61, None),
+        ];
+        for (scoring, l_score, r_score, secs, expected) in table {
+            let winner = rules(scoring).winner(l_score, r_score, Duration::from_secs(secs));
+            assert!(
+                winner == expected,
+                "{scoring} {l_score}-{r_score} after {secs}s"
+            );
+        }
+    }
+
+    #[test]
+    fn deuce_and_advantage() {
+        let table = [
+            (ScoringRule::WinByTwo, 9, 9, false, None),
+            (ScoringRule::WinByTwo, 10, 9, false, None),
+            (ScoringRule::WinByTwo, 10, 10, true, None),
+            (ScoringRule::WinByTwo, 11, 10, false, L),
+            (ScoringRule::WinByTwo, 10, 11, false, R),
+            (ScoringRule::WinByTwo, 12, 12, true, None),
+            (ScoringRule::WinByTwo, 12, 13, false, R),
+            (ScoringRule::WinByTwo, 13, 11, false, None),
+            (ScoringRule::FirstTo, 10, 10, false, None),
+            (ScoringRule::FirstTo, 11, 10, false, None),
+            (ScoringRule::Timed, 12, 12, false, None),
+        ];
+        for (scoring, l_score, r_score, deuce, advantage) in table {
+            let rules = rules(scoring);
+            assert_eq!(
+                rules.is_deuce(l_score, r_score),
+                deuce,
+                "{scoring} {l_score}-{r_score}"
+            );
+            assert!(
+                rules.advantage(l_score, r_score) == advantage,
+                "{scoring} {l_score}-{r_score}"
+            );
+        }
+    }
+
+    #[test]
+    fn sudden_death() {
+        let table = [
+            (ScoringRule::Timed, 3, 3, 59, false),
+            (ScoringRule::Timed, 3, 3, 60, true),
+            (ScoringRule::Timed, 0, 0, 90, true),
+            (ScoringRule::Timed, 4, 3, 60, false),
+            (ScoringRule::FirstTo, 3, 3, 60, false),
+            (ScoringRule::WinByTwo, 10, 10, 60, false),
+        ];
+        for (scoring, l_score, r_score, secs, expected) in table {
+            let elapsed = Duration::from_secs(secs);
+            assert_eq!(
+                rules(scoring).is_sudden_death(l_score, r_score, elapsed),
+                expected,
+                "{scoring} {l_score}-{r_score} after {secs}s"
+            );
+        }
+    }
+
+    #[test]
+    fn server() {
+        let table = [
+            (ServerRule::Random, Side::Left, 3, L, None),
+            (ServerRule::Loser, Side::Left, 0, None, L),
+            (ServerRule::Loser, Side::Right, 0, None, R),
+            (ServerRule::Loser, Side::Left, 5, R, R),
+            (ServerRule::Loser, Side::Right, 5, L, L),
+            (ServerRule::Fixed(Side::Right), Side::Left, 0, L, R),
+            (ServerRule::Fixed(Side::Left), Side::Right, 7, None, L),
+            (ServerRule::Rotation(1), Side::Left, 0, None, L),
+            (ServerRule::Rotation(1), Side::Left, 1, L, R),
+            (ServerRule::Rotation(1), Side::Left, 2, L, L),
+            (ServerRule::Rotation(2), Side::Left, 1, L, L),
+            (ServerRule::Rotation(2), Side::Left, 2, L, R),
+            (ServerRule::Rotation(2), Side::Left, 3, L, R),
+            (ServerRule::Rotation(2), Side::Left, 4, R, L),
+            (ServerRule::Rotation(2), Side::Right, 2, None, L),
+            (ServerRule::Rotation(5), Side::Right, 4, None, R),
+            (ServerRule::Rotation(5), Side::Right, 5, None, L),
+            (ServerRule::Rotation(5), Side::Right, 10, None, R),
+            (
+                ServerRule::Rotation(MAX_SERVE_ROTATION),
+                Side::Left,
+                MAX_SERVE_ROTATION - 1,
+                None,
+                L,
+            ),
+            (
+                ServerRule::Rotation(MAX_SERVE_ROTATION),
+                Side::Left,
+                MAX_SERVE_ROTATION,
+                None,
+                R,
+            ),
+        ];
+        for (rule, first_server, points_played, last_point_loser, expected) in table {
+            let rules = MatchRules {
+                server: rule,
+                ..MatchRules::default()
+            };
+            let first = if first_server == Side::Left { "left" } else { "right" };
+            assert!(
+                rules.server(first_server, points_played, last_point_loser) == expected,
+                "{}, {first} first, after {points_played} points",
+                rule.to_string().trim()
+            );
+        }
+    }
+
+    #[test]
+    fn server_rules_read_back() {
+        for value in 0..ServerRule::len() as i8 {
+            assert!(ServerRule::try_from(value).is_ok(), "{value}");
+        }
+        assert!(ServerRule::try_from(ServerRule::len() as i8).is_err());
+        assert!(ServerRule::try_from(-1).is_err());
+        assert!(ServerRule::try_from(4) == Ok(ServerRule::Rotation(1)));
+        assert!(
+            ServerRule::try_from(ServerRule::len() as i8 - 1)
+                == Ok(ServerRule::Rotation(MAX_SERVE_ROTATION))
+        );
+    }
+}
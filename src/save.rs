@@ -0,0 +1,520 @@
+use crate::arena::Arena;
+use crate::difficulty::DifficultyParams;
+use crate::game::{Game, GameMode, Side, BRICK_COLUMNS, BRICK_ROWS, MAX_TRAIL_LENGTH, MIN_FIELD};
+use crate::rules::*;
+use crate::settings::config_dir;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Bumped whenever the save format changes, saves of other versions are turned down.
+pub const SAVE_VERSION: u32 = 4;
+
+const HEADER: &str = "pong-tui save";
+
+/// A match put aside with "Save & Quit", along with who played it.
+pub struct SavedMatch {
+    pub game: Game,
+    /// `Player`s of the left and right paddles, as picked in the setup menu.
+    pub players: [i8; 2],
+}
+
+/// Where the saved match lives, `save.txt` in the config directory.
+pub fn save_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("save.txt"))
+}
+
+/// Writes the match to the save file, replacing the one there.
+pub fn store(game: &mut Game, players: [i8; 2]) -> Result<(), String> {
+    let path = save_path().ok_or("no config directory to save to")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    }
+    fs::write(&path, encode(game, players)).map_err(|err| err.to_string())
+}
+
+/// Reads the save file, `Ok(None)` when there is none.
+pub fn load() -> Result<Option<SavedMatch>, String> {
+    let Some(path) = save_path() else {
+        return Ok(None);
+    };
+    match fs::read_to_string(path) {
+        Ok(text) => decode(&text).map(Some),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+pub fn remove() {
+    if let Some(path) = save_path() {
+        let _ = fs::remove_file(path);
+    }
+}
+
+fn server_rule_index(rule: ServerRule) -> i8 {
+    match rule {
+        ServerRule::Random => 0,
+        ServerRule::Loser => 1,
+        ServerRule::Fixed(Side::Right) => 3,
+        ServerRule::Fixed(_) => 2,
+        ServerRule::Rotation(points) => 3 + points as i8,
+    }
+}
+
+fn side_name(side: Option<Side>) -> String {
+    side.map_or("-".to_string(), |side| side.index().to_string())
+}
+
+fn join<T: ToString>(values: impl IntoIterator<Item = T>) -> String {
+    values
+        .into_iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// One `name_<side>: <name>` line per side, empty for sides without a name.
+fn names(game: &Game) -> String {
+    Side::ALL
+        .iter()
+        .map(|side| {
+            format!(
+                "name_{}: {}",
+                side.index(),
+                game.names[side.index()].as_deref().unwrap_or_default()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Turns the match into `key: value` lines, followed by the arena in its level format.
+///
+/// The random generator can't be written out, so it is reseeded from itself and the seed saved,
+/// which leaves the saved match and this one rolling the same numbers.
+pub fn encode(game: &mut Game, players: [i8; 2]) -> String {
+    let seed: u64 = game.rng.gen();
+    game.rng = StdRng::seed_from_u64(seed);
+
+    let rules = game.rules;
+    let lines = [
+        format!("{HEADER} {SAVE_VERSION}"),
+        game.difficulty.to_source().trim_end().to_string(),
+        format!("mode: {}", game.mode as i8),
+        format!("scoring: {}", rules.scoring as i8),
+        format!("win_score: {}", rules.win_score),
+        format!("time_limit: {}", rules.time_limit.as_millis()),
+        format!("server_rule: {}", server_rule_index(rules.server)),
+        format!("serve_control: {}", rules.serve_control as i8),
+        format!(
+            "handicaps: {}",
+            join(rules.handicaps.map(|handicap| format!(
+                "{} {} {}",
+                handicap.length, handicap.speed, handicap.bonus
+            )))
+        ),
+        format!("players: {}", join(players)),
+        names(game),
+        format!("seed: {seed}"),
+        format!("ticks: {}", game.ticks),
+        format!("teleported: {}", game.teleported),
+        format!("field: {} {}", game.field.0, game.field.1),
+        format!("paddles: {}", join(game.paddles)),
+        format!("ball: {}", join(game.b_pos)),
+        format!("velocity: {}", join(game.b_vel)),
+        format!("speed: {}", game.speed),
+        format!("trail: {}", join(game.trail.iter().flatten())),
+        format!("scores: {}", join(game.scores)),
+        format!("lives: {}", join(game.lives)),
+        format!("streak: {} {}", game.streak, game.best_streak),
+        format!(
+            "bricks: {}",
+            game.bricks
+                .iter()
+                .map(|standing| if *standing { '1' } else { '0' })
+                .collect::<String>()
+        ),
+        format!("last_hitter: {}", side_name(game.last_hitter)),
+        format!("first_server: {}", side_name(Some(game.first_server))),
+        format!("last_point_loser: {}", side_name(game.last_point_loser)),
+        format!("server: {}", side_name(Some(game.server))),
+        format!("serve_timer: {}", game.serve_timer.as_millis()),
+        format!("awaiting_serve: {}", game.awaiting_serve),
+        format!("elapsed: {}", game.elapsed.as_millis()),
+        "arena:".to_string(),
+        game.arena.to_source(),
+    ];
+    lines.join("\n")
+}
+
+/// Values of a save by key, handing them out parsed.
+struct Fields<'a>(HashMap<&'a str, &'a str>);
+
+impl<'a> Fields<'a> {
+    fn text(&self, key: &str) -> Result<&'a str, String> {
+        self.0.get(key).copied().ok_or(format!("missing `{key}`"))
+    }
+
+    fn value<T: FromStr>(&self, key: &str) -> Result<T, String> {
+        let text = self.text(key)?;
+        text.parse()
+            .map_err(|_| format!("`{key}` has a bad value `{text}`"))
+    }
+
+    fn values<T: FromStr>(&self, key: &str) -> Result<Vec<T>, String> {
+        let text = self.text(key)?;
+        text.split_whitespace()
+            .map(|value| value.parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| format!("`{key}` has a bad value `{text}`"))
+    }
+
+    fn array<T: FromStr, const N: usize>(&self, key: &str) -> Result<[T; N], String> {
+        self.values(key)?
+            .try_into()
+            .map_err(|_| format!("`{key}` needs {N} values"))
+    }
+
+    fn duration(&self, key: &str) -> Result<Duration, String> {
+        self.value(key).map(Duration::from_millis)
+    }
+
+    fn side(&self, key: &str) -> Result<Option<Side>, String> {
+        match self.text(key)? {
+            "-" => Ok(None),
+            index => index
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| Side::ALL.get(index).copied())
+                .map(Some)
+                .ok_or(format!("`{key}` has a bad side `{index}`")),
+        }
+    }
+
+    /// Reads the difficulty lines, which are written the way the custom difficulty is.
+    fn difficulty(&self) -> Result<DifficultyParams, String> {
+        let source = DifficultyParams::KEYS
+            .iter()
+            .map(|key| Ok(format!("{key}: {}", self.text(key)?)))
+            .collect::<Result<Vec<_>, String>>()?;
+        DifficultyParams::parse(&source.join("\n")).map_err(|err| format!("difficulty {err}"))
+    }
+
+    /// Reads the length, speed and bonus of every side in turn.
+    fn handicaps(&self) -> Result<[Handicap; 4], String> {
+        let values: [i16; 12] = self.array("handicaps")?;
+        let mut handicaps = [Handicap::default(); 4];
+        for (handicap, values) in handicaps.iter_mut().zip(values.chunks_exact(3)) {
+            let [length, speed, bonus] = [values[0], values[1], values[2]];
+            if !(MIN_HANDICAP_LENGTH as i16..=MAX_HANDICAP_LENGTH as i16).contains(&length)
+                || !(1..=MAX_HANDICAP_SPEED as i16).contains(&speed)
+                || !(0..=MAX_HANDICAP_BONUS as i16).contains(&bonus)
+            {
+                return Err("`handicaps` is out of range".to_string());
+            }
+            *handicap = Handicap {
+                length: length as i8,
+                speed: speed as u8,
+                bonus: bonus as u16,
+            };
+        }
+        Ok(handicaps)
+    }
+
+    fn choice<T: TryFrom<i8>>(&self, key: &str) -> Result<T, String> {
+        T::try_from(self.value(key)?).map_err(|_| format!("`{key}` is out of range"))
+    }
+}
+
+pub fn decode(text: &str) -> Result<SavedMatch, String> {
+    let (head, arena) = text.split_once("\narena:\n").ok_or("missing arena")?;
+    let mut lines = head.lines();
+
+    let version = lines
+        .next()
+        .and_then(|line| line.strip_prefix(HEADER))
+        .and_then(|version| version.trim().parse::<u32>().ok())
+        .ok_or("not a pong-tui save")?;
+    if version != SAVE_VERSION {
+        return Err(format!(
+            "the save is version {version}, this pong-tui reads version {SAVE_VERSION}"
+        ));
+    }
+
+    let fields = Fields(
+        lines
+            .map(|line| {
+                line.split_once(':')
+                    .map(|(key, value)| (key.trim(), value.trim()))
+                    .ok_or(format!("expected `key: value`, found `{line}`"))
+            })
+            .collect::<Result<_, _>>()?,
+    );
+
+    let mode: GameMode = fields.choice("mode")?;
+    let rules = MatchRules {
+        scoring: fields.choice::<ScoringRule>("scoring")?,
+        win_score: fields.value("win_score")?,
+        time_limit: fields.duration("time_limit")?,
+        server: fields.choice::<ServerRule>("server_rule")?,
+        serve_control: fields.choice::<ServeControl>("serve_control")?,
+        handicaps: fields.handicaps()?,
+    };
+    let arena = Arena::parse(arena).map_err(|err| format!("arena: {err}"))?;
+    let mut game = Game::new(fields.difficulty()?, mode, rules, arena);
+
+    game.rng = StdRng::seed_from_u64(fields.value("seed")?);
+    for side in Side::ALL {
+        let name = fields.text(&format!("name_{}", side.index()))?;
+        game.names[side.index()] = (!name.is_empty()).then(|| name.to_string());
+    }
+    game.ticks = fields.value("ticks")?;
+    game.teleported = fields.value("teleported")?;
+    game.field = fields.array::<u16, 2>("field")?.into();
+    game.paddles = fields.array("paddles")?;
+    game.b_pos = fields.array("ball")?;
+    game.b_vel = fields.array("velocity")?;
+    game.speed = fields.value("speed")?;
+    if game.field.0 < MIN_FIELD || game.field.1 < MIN_FIELD {
+        return Err("`field` is too small".to_string());
+    }
+    let on_field = |pos: &f32| (0.0..=1.0).contains(pos);
+    if !game.paddles.iter().all(on_field) {
+        return Err("`paddles` are off the field".to_string());
+    }
+    if !game.b_pos.iter().all(on_field) {
+        return Err("`ball` is off the field".to_string());
+    }
+    if !game.b_vel.iter().all(|v| (-1..=1).contains(v)) {
+        return Err("`velocity` is out of range".to_string());
+    }
+    game.trail = fields
+        .values::<f32>("trail")?
+        .chunks_exact(2)
+        .map(|pos| [pos[0], pos[1]])
+        .take(MAX_TRAIL_LENGTH)
+        .collect();
+    game.scores = fields.array("scores")?;
+    game.lives = fields.array("lives")?;
+    [game.streak, game.best_streak] = fields.array("streak")?;
+    game.bricks = fields.text("bricks")?.chars().map(|c| c == '1').collect();
+    let brick_count = match mode {
+        GameMode::Breakout => BRICK_COLUMNS * BRICK_ROWS,
+        _ => 0,
+    };
+    if game.bricks.len() != brick_count {
+        return Err("`bricks` don't match the mode".to_string());
+    }
+    game.last_hitter = fields.side("last_hitter")?;
+    game.first_server = fields.side("first_server")?.ok_or("missing first server")?;
+    game.last_point_loser = fields.side("last_point_loser")?;
+    game.server = fields.side("server")?.ok_or("missing server")?;
+    game.serve_timer = fields.duration("serve_timer")?;
+    game.awaiting_serve = fields.value("awaiting_serve")?;
+    game.elapsed = fields.duration("elapsed")?;
+
+    Ok(SavedMatch {
+        game,
+        players: fields.array("players")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs the match for `steps` physics steps, the way a headless simulation does.
+    fn play(game: &mut Game, steps: usize) {
+        for _ in 0..steps {
+            game.advance_clock(game.serve_timer.max(game.step_time()));
+            game.step();
+            game.take_events();
+            game.check_game_over();
+        }
+    }
+
+    /// A versus match a few points in, on an arena with moving walls.
+    fn mid_match() -> Game {
+        let rules = MatchRules {
+            scoring: ScoringRule::WinByTwo,
+            win_score: MAX_WIN_SCORE,
+            server: ServerRule::Rotation(2),
+            handicaps: [
+                Handicap {
+                    length: 2,
+                    speed: 2,
+                    bonus: 1,
+                },
+                Handicap::default(),
+                Handicap::default(),
+                Handicap {
+                    length: -1,
+                    speed: 3,
+                    bonus: 0,
+                },
+            ],
+            ..MatchRules::default()
+        };
+        let arena = Arena::parse(include_str!("../arenas/moving_walls.txt")).unwrap();
+        let mut game = Game::new(DifficultyParams::default(), GameMode::Versus, rules, arena);
+        game.names[Side::Left.index()] = Some("Ada".to_string());
+        game.resize(60, 20);
+        game.full_reset();
+        while game.scores.iter().sum::<u16>() < 3 {
+            play(&mut game, 1);
+        }
+        play(&mut game, 7);
+        assert!(!game.game_over);
+        game
+    }
+
+    /// Everything a save keeps but the match clock, in a form that can be compared.
+    fn state(game: &Game) -> String {
+        [
+            game.difficulty.to_source(),
+            format!("{:?}", game.names),
+            format!("{:?}", game.field),
+            format!("{:?}", game.ticks),
+            format!("{:?}", game.teleported),
+            format!("{:?}", game.paddles),
+            format!("{:?}", game.b_pos),
+            format!("{:?}", game.b_vel),
+            format!("{:?}", game.trail),
+            format!("{:?}", game.speed),
+            format!("{:?}", game.scores),
+            format!("{:?}", game.lives),
+            format!("{:?}", (game.streak, game.best_streak)),
+            format!("{:?}", game.mode as i8),
+            format!("{:?}", server_rule_index(game.rules.server)),
+            format!("{:?}", game.bricks),
+            format!("{:?}", game.last_hitter.map(|side| side.index())),
+            format!("{:?}", (game.first_server.index(), game.server.index())),
+            format!("{:?}", game.last_point_loser.map(|side| side.index())),
+            format!("{:?}", game.serve_timer.as_millis()),
+            format!("{:?}", game.awaiting_serve),
+            format!("{:?}", game.arena.to_source()),
+        ]
+        .join(" ")
+    }
+
+    #[test]
+    fn mid_match_survives_a_round_trip() {
+        let mut game = mid_match();
+        let text = encode(&mut game, [0, 3]);
+        let saved = decode(&text).unwrap();
+        let mut loaded = saved.game;
+
+        assert_eq!(saved.players, [0, 3]);
+        assert_eq!(state(&loaded), state(&game));
+        assert_eq!(loaded.elapsed.as_millis(), game.elapsed.as_millis());
+        assert!(loaded.rules.handicaps == game.rules.handicaps);
+        assert!(loaded.rules.scoring == game.rules.scoring);
+        assert_eq!(loaded.rules.win_score, game.rules.win_score);
+
+        // Both carry on the same way, random serves included.
+        play(&mut game, 500);
+        play(&mut loaded, 500);
+        assert_eq!(state(&loaded), state(&game));
+        // The clock is saved to the millisecond.
+        assert!(loaded.elapsed.abs_diff(game.elapsed) < Duration::from_millis(1));
+        assert_eq!(loaded.rng.gen::<u64>(), game.rng.gen::<u64>());
+    }
+
+    /// `text` with the line of `key` replaced by `line`.
+    fn with_line(text: &str, key: &str, line: &str) -> String {
+        let prefix = format!("{key}:");
+        text.lines()
+            .map(|old| if old.starts_with(&prefix) { line } else { old })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn other_versions_are_turned_down() {
+        let text = encode(&mut mid_match(), [0, 0]);
+        for version in [0, SAVE_VERSION - 1, SAVE_VERSION + 1] {
+            let old = text.replacen(
+                &format!("{HEADER} {SAVE_VERSION}"),
+                &format!("{HEADER} {version}"),
+                1,
+            );
+            let err = decode(&old).err().unwrap();
+            assert!(err.contains(&format!("version {version}")), "{err}");
+        }
+        assert!(decode(&text.replacen(HEADER, "pong save", 1)).is_err());
+    }
+
+    #[test]
+    fn bad_values_are_errors() {
+        let text = encode(&mut mid_match(), [0, 0]);
+        assert!(decode(&text).is_ok());
+
+        let bad_lines = [
+            ("handicaps", "handicaps: 2 2 1 0 1 0 0 1 0 -1 3"),
+            ("handicaps", "handicaps: 2 2 1 0 1 0 0 1 0 -1 3 0 0"),
+            ("handicaps", "handicaps: 2 2 1 0 1 0 0 1 0 -1 x 0"),
+            ("handicaps", "handicaps: 2 2 1 0 1 0 0 1 0 -1 4 0"),
+            ("handicaps", "handicaps: 99999 2 1 0 1 0 0 1 0 -1 3 0"),
+            ("handicaps", "handicaps:"),
+            ("mode", "mode: 4"),
+            ("mode", "mode: -1"),
+            ("mode", "mode: 300"),
+            ("scoring", "scoring: 3"),
+            ("scoring", "scoring: first"),
+            ("server_rule", "server_rule: 14"),
+            ("server_rule", "server_rule: 127"),
+            ("serve_control", "serve_control: 2"),
+            ("serve_control", "serve_control"),
+            ("players", "players: 0"),
+            ("first_server", "first_server: 4"),
+            ("server", "server: -"),
+            ("field", "field: 7 20"),
+            ("field", "field: 60 0"),
+            ("paddles", "paddles: 0.5 -0.1 0.5 0.5"),
+            ("paddles", "paddles: 0.5 0.5 NaN 0.5"),
+            ("ball", "ball: 1.5 0.5"),
+            ("ball", "ball: 0.5 -0.5"),
+            ("velocity", "velocity: 2 0"),
+            ("velocity", "velocity: 1 -30000"),
+            ("bricks", "bricks: 1"),
+        ];
+        for (key, line) in bad_lines {
+            assert!(decode(&with_line(&text, key, line)).is_err(), "{line}");
+        }
+        for key in [
+            "handicaps",
+            "mode",
+            "scoring",
+            "server_rule",
+            "serve_control",
+        ] {
+            assert!(decode(&with_line(&text, key, "")).is_err(), "{key}");
+        }
+
+        let mut breakout = Game::new(
+            DifficultyParams::default(),
+            GameMode::Breakout,
+            MatchRules::default(),
+            Arena::default(),
+        );
+        breakout.resize(60, 20);
+        breakout.full_reset();
+        let text = encode(&mut breakout, [0, 0]);
+        assert!(decode(&text).is_ok());
+        assert!(decode(&with_line(&text, "bricks", "bricks: 1")).is_err());
+    }
+
+    #[test]
+    fn truncated_saves_are_errors() {
+        let text = encode(&mut mid_match(), [0, 0]);
+        let arena = text.find("\narena:\n").unwrap();
+        for (end, _) in text.char_indices().take_while(|&(end, _)| end <= arena) {
+            assert!(decode(&text[..end]).is_err(), "{}", &text[..end]);
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use crate::font::{self, FontStyle, Glyphs};
+use crate::theme::{ColorDepth, Palette, Theme};
+use std::env;
+use std::path::PathBuf;
+
+pub const MAX_VOLUME: u8 = 10;
+pub const MIN_GAME_SPEED: u8 = 5;
+pub const MAX_GAME_SPEED: u8 = 10;
+
+/// Preferences that apply to every match, edited from the settings menu.
+pub struct Settings {
+    pub reduced_motion: bool,
+    /// How many previous ball positions are drawn behind the ball, 0 turns the trail off.
+    pub ball_trail: u8,
+    pub muted: bool,
+    pub volume: u8,
+    /// Style of the big digits used for scores and clocks.
+    pub font_style: FontStyle,
+    pub theme: Theme,
+    /// Titles and winners blink to catch the eye, off for people bothered by it.
+    pub blinking: bool,
+    /// Draws the ball a full cell tall and the paddles two cells thick.
+    pub large_glyphs: bool,
+    /// Tenths of the full speed matches play at, slowing them down whatever the difficulty.
+    pub game_speed: u8,
+    /// Draws the menus and popups as plain lines of text, read more easily by screen readers.
+    pub text_mode: bool,
+    /// Draws with plain ASCII instead of block elements and box drawing.
+    pub ascii: bool,
+    pub color_depth: ColorDepth,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            reduced_motion: false,
+            ball_trail: 0,
+            muted: false,
+            volume: MAX_VOLUME,
+            font_style: FontStyle::Auto,
+            theme: Theme::Classic,
+            blinking: true,
+            large_glyphs: false,
+            game_speed: MAX_GAME_SPEED,
+            text_mode: false,
+            ascii: false,
+            color_depth: ColorDepth::TrueColor,
+        }
+    }
+}
+
+impl Settings {
+    pub fn palette(&self) -> &'static Palette {
+        self.theme.palette()
+    }
+
+    pub fn glyphs(&self) -> &'static Glyphs {
+        if self.ascii {
+            &font::ASCII
+        } else {
+            &font::UNICODE
+        }
+    }
+}
+
+/// Whether the terminal can be trusted with Unicode glyphs: it isn't the Linux console or a bare
+/// VT, and the first of `LC_ALL`, `LC_CTYPE` and `LANG` that is set names UTF-8.
+pub fn unicode_supported() -> bool {
+    let term = env::var("TERM").unwrap_or_default();
+    if term == "linux" || term == "dumb" || term.starts_with("vt") {
+        return false;
+    }
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|name| env::var(name).ok().filter(|value| !value.is_empty()))
+        .is_some_and(|locale| {
+            let locale = locale.to_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        })
+}
+
+/// Where user files live, `$XDG_CONFIG_HOME/pong-tui` or `~/.config/pong-tui`.
+pub fn config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("pong-tui"))
+}
@@ -0,0 +1,309 @@
+use crate::arena::Arena;
+use crate::controller::{External, PaddleController, Predictor, Tracker};
+use crate::difficulty::{self, Difficulty, DifficultyParams};
+use crate::game::{Game, GameEvent, GameMode, Side};
+use crate::rules::MatchRules;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::io::{Error, ErrorKind, Result};
+
+pub const USAGE: &str = "\
+Usage: pong-tui simulate --left <bot> --right <bot> [options]
+
+Plays matches without a terminal as fast as possible and prints the results.
+
+Bots:
+    tracker                      follows the ball
+    easy, normal, hard, insane   the computer player of that difficulty
+    custom                       the computer player of the difficulty edited in the setup menu
+    bot:<command>                an external bot, see the readme
+
+Options:
+    --games <n>         matches to play, 100 by default
+    --seed <n>          makes the run repeatable, random by default
+    --difficulty <d>    easy, normal, hard, insane, custom or all, which spreads the matches over
+                        every difficulty but custom, all by default
+    --win-score <n>     points needed to win a match, 10 by default
+    --json              prints JSON instead of a table";
+
+/// Steps after which a match between two players that never miss is called off.
+pub const MAX_MATCH_STEPS: u64 = 1_000_000;
+
+/// Field size the matches are played on, the size of a classic terminal.
+pub const FIELD: (u16, u16) = (80, 24);
+
+struct Options {
+    left: String,
+    right: String,
+    games: u32,
+    seed: u64,
+    difficulties: Vec<Difficulty>,
+    win_score: u16,
+    json: bool,
+}
+
+/// Results of the matches played at one difficulty.
+#[derive(Default)]
+struct Tally {
+    games: u32,
+    wins: [u32; 2],
+    unfinished: u32,
+    points: [u64; 2],
+    returns: u64,
+}
+
+impl Tally {
+    fn add(&mut self, other: &Tally) {
+        self.games += other.games;
+        self.wins[0] += other.wins[0];
+        self.wins[1] += other.wins[1];
+        self.unfinished += other.unfinished;
+        self.points[0] += other.points[0];
+        self.points[1] += other.points[1];
+        self.returns += other.returns;
+    }
+
+    fn win_rate(&self, side: usize) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        self.wins[side] as f64 / self.games as f64
+    }
+
+    /// Average paddle hits per point.
+    fn average_rally(&self) -> f64 {
+        let points = self.points[0] + self.points[1];
+        if points == 0 {
+            return 0.0;
+        }
+        self.returns as f64 / points as f64
+    }
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidInput, message)
+}
+
+fn difficulty_name(difficulty: Difficulty) -> String {
+    difficulty.to_string().trim().to_lowercase()
+}
+
+fn parse_difficulty(name: &str) -> Option<Difficulty> {
+    (0..Difficulty::len() as i8)
+        .filter_map(|i| Difficulty::try_from(i).ok())
+        .find(|difficulty| difficulty_name(*difficulty) == name)
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options> {
+    let mut options = Options {
+        left: String::new(),
+        right: String::new(),
+        games: 100,
+        seed: rand::random(),
+        difficulties: Difficulty::PRESETS.to_vec(),
+        win_score: 10,
+        json: false,
+    };
+
+    while let Some(arg) = args.next() {
+        if arg == "--json" {
+            options.json = true;
+            continue;
+        }
+
+        let value = args
+            .next()
+            .ok_or_else(|| invalid(format!("`{arg}` needs a value")))?;
+        let number = |value: &str| {
+            value
+                .parse::<u64>()
+                .map_err(|_| invalid(format!("`{value}` is not a number")))
+        };
+        match arg.as_str() {
+            "--left" => options.left = value,
+            "--right" => options.right = value,
+            "--games" => {
+                options.games = u32::try_from(number(&value)?)
+                    .map_err(|_| invalid(format!("`{value}` is too many games")))?
+            }
+            "--seed" => options.seed = number(&value)?,
+            "--win-score" => options.win_score = number(&value)?.clamp(1, u16::MAX as u64) as u16,
+            "--difficulty" if value == "all" => (),
+            "--difficulty" => {
+                options.difficulties = vec![parse_difficulty(&value)
+                    .ok_or_else(|| invalid(format!("unknown difficulty `{value}`")))?]
+            }
+            _ => return Err(invalid(format!("unknown option `{arg}`"))),
+        }
+    }
+
+    if options.left.is_empty() || options.right.is_empty() {
+        return Err(invalid("both --left and --right are needed".to_string()));
+    }
+    Ok(options)
+}
+
+fn controller(bot: &str, custom: DifficultyParams, seed: u64) -> Result<Box<dyn PaddleController>> {
+    if bot == "tracker" {
+        return Ok(Box::new(Tracker));
+    }
+    if let Some(difficulty) = parse_difficulty(bot) {
+        let params = difficulty.preset().unwrap_or(custom);
+        return Ok(Box::new(Predictor::seeded(difficulty, params, seed)));
+    }
+    if let Some(command) = bot.strip_prefix("bot:") {
+        return Ok(Box::new(External::spawn(command, true)));
+    }
+    Err(invalid(format!("unknown bot `{bot}`")))
+}
+
+/// Plays one match to the end and adds it to `tally`.
+fn play(game: &mut Game, controllers: &mut [Box<dyn PaddleController>; 2], tally: &mut Tally) {
+    // The controllers are lent to the match, so bots keep running from one match to the next.
+    for (i, side) in [Side::Left, Side::Right].into_iter().enumerate() {
+        std::mem::swap(&mut game.controllers[side.index()], &mut controllers[i]);
+    }
+    game.resize(FIELD.0, FIELD.1);
+    game.full_reset();
+
+    tally.games += 1;
+    let mut finished = false;
+    for _ in 0..MAX_MATCH_STEPS {
+        // Nobody is watching the serve countdown.
+        game.advance_clock(game.serve_timer.max(game.step_time()));
+        game.step();
+        for event in game.take_events() {
+            if let GameEvent::PaddleHit(..) = event {
+                tally.returns += 1;
+            }
+        }
+
+        game.check_game_over();
+        if game.game_over {
+            finished = true;
+            break;
+        }
+    }
+
+    match game.winner() {
+        Some(Side::Left) if finished => tally.wins[0] += 1,
+        Some(Side::Right) if finished => tally.wins[1] += 1,
+        _ => tally.unfinished += 1,
+    }
+    tally.points[0] += game.score(Side::Left) as u64;
+    tally.points[1] += game.score(Side::Right) as u64;
+
+    for (i, side) in [Side::Left, Side::Right].into_iter().enumerate() {
+        std::mem::swap(&mut game.controllers[side.index()], &mut controllers[i]);
+    }
+}
+
+pub fn run(args: impl Iterator<Item = String>) -> Result<()> {
+    let options = parse_options(args)?;
+    let custom = difficulty::load_custom()
+        .map_err(|err| invalid(format!("custom difficulty: {err}")))?
+        .unwrap_or_default();
+    let mut controllers = [
+        controller(&options.left, custom, options.seed.wrapping_add(1))?,
+        controller(&options.right, custom, options.seed.wrapping_add(2))?,
+    ];
+    let rules = MatchRules {
+        win_score: options.win_score,
+        ..MatchRules::default()
+    };
+
+    let mut tallies: Vec<(Difficulty, Tally)> = options
+        .difficulties
+        .iter()
+        .map(|difficulty| (*difficulty, Tally::default()))
+        .collect();
+    for i in 0..options.games {
+        let (difficulty, tally) = &mut tallies[i as usize % options.difficulties.len()];
+        let params = difficulty.preset().unwrap_or(custom);
+        let mut game = Game::new(params, GameMode::Versus, rules, Arena::default());
+        game.rng = StdRng::seed_from_u64(options.seed.wrapping_add(i as u64));
+        play(&mut game, &mut controllers, tally);
+    }
+
+    let mut total = Tally::default();
+    for (_, tally) in tallies.iter() {
+        total.add(tally);
+    }
+
+    if options.json {
+        print_json(&options, &tallies, &total);
+    } else {
+        print_table(&options, &tallies, &total);
+    }
+    Ok(())
+}
+
+fn print_table(options: &Options, tallies: &[(Difficulty, Tally)], total: &Tally) {
+    println!(
+        "{} (left) vs {} (right), {} games, seed {}",
+        options.left, options.right, options.games, options.seed
+    );
+    println!();
+    println!(
+        "{: <10} {: >6} {: >10} {: >10} {: >10} {: >10} {: >10}",
+        "Difficulty", "Games", "Left wins", "Right wins", "Left pts", "Right pts", "Avg rally"
+    );
+
+    let row = |name: &str, tally: &Tally| {
+        println!(
+            "{: <10} {: >6} {: >9.1}% {: >9.1}% {: >10} {: >10} {: >10.2}",
+            name,
+            tally.games,
+            tally.win_rate(0) * 100.0,
+            tally.win_rate(1) * 100.0,
+            tally.points[0],
+            tally.points[1],
+            tally.average_rally(),
+        );
+    };
+    for (difficulty, tally) in tallies {
+        row(difficulty.to_string().trim(), tally);
+    }
+    if tallies.len() > 1 {
+        row("All", total);
+    }
+
+    if total.unfinished > 0 {
+        println!();
+        println!(
+            "{} games were called off after {MAX_MATCH_STEPS} steps.",
+            total.unfinished
+        );
+    }
+}
+
+fn print_json(options: &Options, tallies: &[(Difficulty, Tally)], total: &Tally) {
+    let result = |name: &str, tally: &Tally| {
+        format!(
+            "{{\"difficulty\":\"{name}\",\"games\":{},\"left_wins\":{},\"right_wins\":{},\"unfinished\":{},\"left_win_rate\":{:.4},\"right_win_rate\":{:.4},\"left_points\":{},\"right_points\":{},\"average_rally\":{:.4}}}",
+            tally.games,
+            tally.wins[0],
+            tally.wins[1],
+            tally.unfinished,
+            tally.win_rate(0),
+            tally.win_rate(1),
+            tally.points[0],
+            tally.points[1],
+            tally.average_rally(),
+        )
+    };
+    let results: Vec<String> = tallies
+        .iter()
+        .map(|(difficulty, tally)| result(&difficulty_name(*difficulty), tally))
+        .chain([result("all", total)])
+        .collect();
+
+    println!(
+        "{{\"left\":\"{}\",\"right\":\"{}\",\"games\":{},\"seed\":{},\"results\":[{}]}}",
+        options.left.replace('\\', "\\\\").replace('"', "\\\""),
+        options.right.replace('\\', "\\\\").replace('"', "\\\""),
+        options.games,
+        options.seed,
+        results.join(",")
+    );
+}
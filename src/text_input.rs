@@ -0,0 +1,188 @@
+/// Something done to the text being typed, other than typing a character.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Edit {
+    Left,
+    Right,
+    Home,
+    End,
+    /// Deletes the character before the cursor.
+    Backspace,
+    /// Deletes the character under the cursor.
+    Delete,
+}
+
+/// A single line of text being typed, with a cursor that can be moved around in it.
+pub struct TextInput {
+    chars: Vec<char>,
+    /// Characters before the cursor.
+    cursor: usize,
+    max_len: usize,
+}
+
+impl TextInput {
+    /// Starts with `text`, cut to `max_len` characters, and the cursor at its end.
+    pub fn new(text: &str, max_len: usize) -> Self {
+        let chars: Vec<char> = text.chars().take(max_len).collect();
+        Self {
+            cursor: chars.len(),
+            chars,
+            max_len,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    /// Types `c` at the cursor, control characters and anything past `max_len` are dropped.
+    pub fn insert(&mut self, c: char) {
+        if c.is_control() || self.chars.len() >= self.max_len {
+            return;
+        }
+        self.chars.insert(self.cursor, c);
+        self.cursor += 1;
+    }
+
+    /// Types the first line of `text`, tabs turned into spaces.
+    pub fn paste(&mut self, text: &str) {
+        let line = text.lines().next().unwrap_or_default();
+        for c in line.chars() {
+            self.insert(if c == '\t' { ' ' } else { c });
+        }
+    }
+
+    pub fn edit(&mut self, edit: Edit) {
+        match edit {
+            Edit::Left => self.cursor = self.cursor.saturating_sub(1),
+            Edit::Right => self.cursor = (self.cursor + 1).min(self.chars.len()),
+            Edit::Home => self.cursor = 0,
+            Edit::End => self.cursor = self.chars.len(),
+            Edit::Backspace => {
+                if self.cursor > 0 {
+                    self.cursor -= 1;
+                    self.chars.remove(self.cursor);
+                }
+            }
+            Edit::Delete => {
+                if self.cursor < self.chars.len() {
+                    self.chars.remove(self.cursor);
+                }
+            }
+        }
+    }
+
+    /// Returns the part of the text that fits in `width` columns with the cursor in view, and
+    /// where the cursor is in it. The cursor takes a column of its own past the end of the text.
+    pub fn view(&self, width: usize) -> (String, usize) {
+        let width = width.max(1);
+        let start = (self.cursor + 1).saturating_sub(width);
+        let text = self.chars.iter().skip(start).take(width).collect();
+        (text, self.cursor - start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edited(text: &str, edits: &[Edit]) -> TextInput {
+        let mut input = TextInput::new(text, 8);
+        for edit in edits {
+            input.edit(*edit);
+        }
+        input
+    }
+
+    #[test]
+    fn the_cursor_stays_within_the_text() {
+        let mut input = TextInput::new("abc", 8);
+        assert_eq!(input.cursor, 3);
+        input.edit(Edit::Right);
+        assert_eq!(input.cursor, 3);
+        input.edit(Edit::Left);
+        assert_eq!(input.cursor, 2);
+        input.edit(Edit::Home);
+        input.edit(Edit::Left);
+        assert_eq!(input.cursor, 0);
+        input.edit(Edit::Right);
+        assert_eq!(input.cursor, 1);
+        input.edit(Edit::End);
+        assert_eq!(input.cursor, 3);
+
+        let mut empty = TextInput::new("", 8);
+        for edit in [Edit::Left, Edit::Right, Edit::Home, Edit::End] {
+            empty.edit(edit);
+            assert_eq!(empty.cursor, 0);
+        }
+    }
+
+    #[test]
+    fn typing_and_deleting_at_either_end() {
+        let mut input = edited("bc", &[Edit::Home]);
+        input.insert('a');
+        input.edit(Edit::End);
+        input.insert('d');
+        assert_eq!(input.text(), "abcd");
+        assert_eq!(input.cursor, 4);
+
+        let table = [
+            (vec![Edit::Home, Edit::Backspace], "abcd", 0),
+            (vec![Edit::End, Edit::Delete], "abcd", 4),
+            (vec![Edit::End, Edit::Backspace], "abc", 3),
+            (vec![Edit::Home, Edit::Delete], "bcd", 0),
+            (vec![Edit::Left, Edit::Left, Edit::Backspace], "acd", 1),
+            (vec![Edit::Left, Edit::Left, Edit::Delete], "abd", 2),
+        ];
+        for (edits, text, cursor) in table {
+            let input = edited("abcd", &edits);
+            assert_eq!((input.text().as_str(), input.cursor), (text, cursor));
+        }
+
+        let mut empty = edited("", &[Edit::Backspace, Edit::Delete]);
+        assert_eq!(empty.text(), "");
+        empty.insert('\n');
+        empty.insert('\u{7f}');
+        assert_eq!(empty.text(), "");
+    }
+
+    #[test]
+    fn pasting_types_the_first_line() {
+        let mut input = edited("ad", &[Edit::Left]);
+        input.paste("b\tc\nignored");
+        assert_eq!(input.text(), "ab cd");
+        assert_eq!(input.cursor, 4);
+
+        input.paste("");
+        assert_eq!(input.text(), "ab cd");
+    }
+
+    #[test]
+    fn text_is_capped_at_the_max_length() {
+        let input = TextInput::new("abcdefghij", 8);
+        assert_eq!(input.text(), "abcdefgh");
+        assert_eq!(input.cursor, 8);
+
+        let mut input = edited("abcdefg", &[Edit::Home]);
+        input.paste("xyz");
+        assert_eq!(input.text(), "xabcdefg");
+        input.insert('!');
+        assert_eq!(input.text(), "xabcdefg");
+
+        // Deleting makes room again.
+        input.edit(Edit::Delete);
+        input.insert('!');
+        assert_eq!(input.text(), "x!bcdefg");
+    }
+
+    #[test]
+    fn the_view_follows_the_cursor() {
+        let input = TextInput::new("abcdef", 8);
+        assert_eq!(input.view(10), ("abcdef".to_string(), 6));
+        assert_eq!(input.view(4), ("def".to_string(), 3));
+        assert_eq!(
+            edited("abcdef", &[Edit::Home]).view(4),
+            ("abcd".to_string(), 0)
+        );
+        assert_eq!(input.view(0), ("".to_string(), 0));
+    }
+}
@@ -0,0 +1,383 @@
+use ratatui::style::Color;
+use std::fmt::Display;
+
+/// The colours used to draw a match.
+///
+/// `trail` goes from the segment right behind the ball to the oldest one.
+pub struct Palette {
+    pub ball: Color,
+    pub trail: [Color; 3],
+    pub paddle: Color,
+    /// Background of the walls and of the goal line either side of a narrowed goal.
+    pub wall: Color,
+    pub block: Color,
+    pub bumper: Color,
+    pub portal: Color,
+    pub bricks: [Color; 5],
+    /// Draws the eye, scores that just changed, the serve countdown and sparks.
+    pub accent: Color,
+    pub winner: Color,
+    /// Background of the goal line that just conceded.
+    pub flash: Color,
+    /// Player names and the key hints.
+    pub label: Color,
+    /// Background of the popups over a match.
+    pub popup: Color,
+}
+
+pub const CLASSIC: Palette = Palette {
+    ball: Color::White,
+    trail: [Color::Gray, Color::DarkGray, Color::DarkGray],
+    paddle: Color::White,
+    wall: Color::DarkGray,
+    block: Color::Gray,
+    bumper: Color::Yellow,
+    portal: Color::Magenta,
+    bricks: [
+        Color::Red,
+        Color::Yellow,
+        Color::Green,
+        Color::Cyan,
+        Color::Magenta,
+    ],
+    accent: Color::Yellow,
+    winner: Color::Green,
+    flash: Color::LightGreen,
+    label: Color::DarkGray,
+    popup: Color::DarkGray,
+};
+
+/// Only white, black and bright yellow, without the greys that fade into the background.
+pub const HIGH_CONTRAST: Palette = Palette {
+    ball: Color::White,
+    trail: [Color::White, Color::Gray, Color::Gray],
+    paddle: Color::White,
+    wall: Color::White,
+    block: Color::White,
+    bumper: Color::LightYellow,
+    portal: Color::LightYellow,
+    bricks: [
+        Color::White,
+        Color::LightYellow,
+        Color::White,
+        Color::LightYellow,
+        Color::White,
+    ],
+    accent: Color::LightYellow,
+    winner: Color::LightYellow,
+    flash: Color::White,
+    label: Color::White,
+    popup: Color::Black,
+};
+
+/// Okabe and Ito's colours, told apart without seeing red from green.
+pub const RED_GREEN_SAFE: Palette = Palette {
+    bumper: Color::Rgb(230, 159, 0),
+    portal: Color::Rgb(86, 180, 233),
+    bricks: [
+        Color::Rgb(230, 159, 0),
+        Color::Rgb(86, 180, 233),
+        Color::Rgb(240, 228, 66),
+        Color::Rgb(0, 114, 178),
+        Color::Rgb(204, 121, 167),
+    ],
+    accent: Color::Rgb(240, 228, 66),
+    winner: Color::Rgb(86, 180, 233),
+    flash: Color::Rgb(0, 114, 178),
+    ..CLASSIC
+};
+
+/// Reds and pinks against white, told apart without seeing blue from yellow.
+pub const BLUE_YELLOW_SAFE: Palette = Palette {
+    bumper: Color::Rgb(213, 94, 0),
+    portal: Color::Rgb(204, 121, 167),
+    bricks: [
+        Color::Rgb(213, 94, 0),
+        Color::White,
+        Color::Rgb(204, 121, 167),
+        Color::Rgb(0, 158, 115),
+        Color::Rgb(255, 160, 160),
+    ],
+    accent: Color::Rgb(255, 160, 160),
+    winner: Color::Rgb(204, 121, 167),
+    flash: Color::Rgb(213, 94, 0),
+    ..CLASSIC
+};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Theme {
+    Classic,
+    HighContrast,
+    RedGreenSafe,
+    BlueYellowSafe,
+}
+
+impl Display for Theme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Classic => write!(f, "Classic"),
+            Self::HighContrast => write!(f, "High Contrast"),
+            Self::RedGreenSafe => write!(f, "Red-Green Safe"),
+            Self::BlueYellowSafe => write!(f, "Blue-Yellow Safe"),
+        }
+    }
+}
+
+impl TryFrom<i8> for Theme {
+    type Error = ();
+    fn try_from(value: i8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Classic),
+            1 => Ok(Self::HighContrast),
+            2 => Ok(Self::RedGreenSafe),
+            3 => Ok(Self::BlueYellowSafe),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Theme {
+    pub fn len() -> u8 {
+        4
+    }
+
+    pub fn palette(&self) -> &'static Palette {
+        match *self {
+            Self::Classic => &CLASSIC,
+            Self::HighContrast => &HIGH_CONTRAST,
+            Self::RedGreenSafe => &RED_GREEN_SAFE,
+            Self::BlueYellowSafe => &BLUE_YELLOW_SAFE,
+        }
+    }
+}
+
+/// How many colours the terminal can show, palettes are brought down to it when drawn.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ColorDepth {
+    /// No colours at all, backgrounds are shown in reverse video instead.
+    Mono,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+/// The 16 ANSI colours, with the values xterm gives them.
+const ANSI: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Levels of every channel in the 6x6x6 cube of the 256 colours.
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    [(r1, r2), (g1, g2), (b1, b2)]
+        .iter()
+        .map(|&(a, b)| (a as i32 - b as i32).pow(2) as u32)
+        .sum()
+}
+
+fn nearest_level(value: u8) -> usize {
+    (0..CUBE.len())
+        .min_by_key(|&i| (CUBE[i] as i32 - value as i32).abs())
+        .unwrap_or(0)
+}
+
+/// The value of one of the 256 colours.
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI[index as usize].1,
+        16..=231 => {
+            let i = index as usize - 16;
+            (CUBE[i / 36], CUBE[i / 6 % 6], CUBE[i % 6])
+        }
+        _ => {
+            let gray = 8 + 10 * (index - 232);
+            (gray, gray, gray)
+        }
+    }
+}
+
+impl ColorDepth {
+    /// Works out the depth from the environment: any `NO_COLOR` turns colours off, `COLORTERM`
+    /// announces true colour and `TERM` the 256 colours.
+    pub fn detect() -> Self {
+        let var = |name| std::env::var(name).unwrap_or_default();
+        if !var("NO_COLOR").is_empty() {
+            return Self::Mono;
+        }
+        let term = var("TERM");
+        if term == "dumb" {
+            Self::Mono
+        } else if matches!(var("COLORTERM").as_str(), "truecolor" | "24bit") {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+
+    /// Reads a depth given on the command line: `truecolor`, `256`, `16` or `mono`.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "truecolor" | "24bit" => Ok(Self::TrueColor),
+            "256" => Ok(Self::Ansi256),
+            "16" => Ok(Self::Ansi16),
+            "mono" | "none" => Ok(Self::Mono),
+            _ => Err(format!(
+                "unknown colour depth \"{name}\", expected truecolor, 256, 16 or mono"
+            )),
+        }
+    }
+
+    /// The closest colour to `color` the terminal can show. Named colours are left to the
+    /// terminal's own palette.
+    pub fn adapt(&self, color: Color) -> Color {
+        let rgb = match (*self, color) {
+            (_, Color::Reset) | (Self::TrueColor, _) => return color,
+            (Self::Mono, _) => return Color::Reset,
+            (_, Color::Rgb(r, g, b)) => (r, g, b),
+            (Self::Ansi16, Color::Indexed(index)) => indexed_rgb(index),
+            _ => return color,
+        };
+
+        if *self == Self::Ansi16 {
+            return ANSI
+                .iter()
+                .min_by_key(|(_, value)| distance(rgb, *value))
+                .map_or(color, |(named, _)| *named);
+        }
+        // The closest of the colour cube and the grey ramp.
+        let (r, g, b) = (
+            nearest_level(rgb.0),
+            nearest_level(rgb.1),
+            nearest_level(rgb.2),
+        );
+        let cube = 16 + 36 * r + 6 * g + b;
+        let average = (rgb.0 as u16 + rgb.1 as u16 + rgb.2 as u16) / 3;
+        let gray = 232 + (average.saturating_sub(3).min(238) / 10) as usize;
+        [cube, gray]
+            .into_iter()
+            .min_by_key(|&index| distance(rgb, indexed_rgb(index as u8)))
+            .map_or(color, |index| Color::Indexed(index as u8))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn depths_parse_by_name() {
+        let table = [
+            ("truecolor", ColorDepth::TrueColor),
+            ("24bit", ColorDepth::TrueColor),
+            ("256", ColorDepth::Ansi256),
+            ("16", ColorDepth::Ansi16),
+            ("mono", ColorDepth::Mono),
+            ("none", ColorDepth::Mono),
+        ];
+        for (name, depth) in table {
+            assert!(ColorDepth::parse(name) == Ok(depth), "{name}");
+        }
+        for name in ["", "8", "TrueColor", "256color"] {
+            assert!(ColorDepth::parse(name).is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn colours_come_down_to_the_depth() {
+        let table = [
+            // Ansi16 picks the closest of the xterm colours.
+            (ColorDepth::Ansi16, Color::Rgb(230, 159, 0), Color::Yellow),
+            (ColorDepth::Ansi16, Color::Rgb(0, 114, 178), Color::Cyan),
+            (ColorDepth::Ansi16, Color::Rgb(250, 10, 10), Color::LightRed),
+            (
+                ColorDepth::Ansi16,
+                Color::Rgb(128, 128, 128),
+                Color::DarkGray,
+            ),
+            (ColorDepth::Ansi16, Color::Indexed(196), Color::LightRed),
+            (ColorDepth::Ansi16, Color::Indexed(232), Color::Black),
+            // Ansi256 picks the closest of the colour cube...
+            (
+                ColorDepth::Ansi256,
+                Color::Rgb(230, 159, 0),
+                Color::Indexed(178),
+            ),
+            (
+                ColorDepth::Ansi256,
+                Color::Rgb(0, 114, 178),
+                Color::Indexed(25),
+            ),
+            (
+                ColorDepth::Ansi256,
+                Color::Rgb(255, 0, 0),
+                Color::Indexed(196),
+            ),
+            (ColorDepth::Ansi256, Color::Rgb(0, 0, 0), Color::Indexed(16)),
+            (
+                ColorDepth::Ansi256,
+                Color::Rgb(255, 255, 255),
+                Color::Indexed(231),
+            ),
+            // ...and the grey ramp.
+            (
+                ColorDepth::Ansi256,
+                Color::Rgb(128, 128, 128),
+                Color::Indexed(244),
+            ),
+            (
+                ColorDepth::Ansi256,
+                Color::Rgb(100, 100, 100),
+                Color::Indexed(241),
+            ),
+            (
+                ColorDepth::Ansi256,
+                Color::Rgb(20, 18, 19),
+                Color::Indexed(233),
+            ),
+            (ColorDepth::Ansi256, Color::Indexed(25), Color::Indexed(25)),
+            // Mono drops every colour, true colour keeps them.
+            (ColorDepth::Mono, Color::Rgb(230, 159, 0), Color::Reset),
+            (ColorDepth::Mono, Color::Red, Color::Reset),
+            (
+                ColorDepth::TrueColor,
+                Color::Rgb(230, 159, 0),
+                Color::Rgb(230, 159, 0),
+            ),
+        ];
+        for (depth, color, expected) in table {
+            assert_eq!(depth.adapt(color), expected, "{color:?}");
+        }
+    }
+
+    #[test]
+    fn named_colours_and_reset_are_left_alone() {
+        for depth in [
+            ColorDepth::Ansi16,
+            ColorDepth::Ansi256,
+            ColorDepth::TrueColor,
+        ] {
+            for color in [Color::Reset, Color::Red, Color::LightBlue, Color::White] {
+                assert_eq!(depth.adapt(color), color);
+            }
+        }
+        assert_eq!(ColorDepth::Mono.adapt(Color::Reset), Color::Reset);
+    }
+}
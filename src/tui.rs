@@ -1,6 +1,12 @@
-use crate::{app::App, drawer, event_handler};
+use crate::{
+    announce,
+    app::{App, ATTRACT_DELAY},
+    drawer, event_handler,
+    settings::MAX_GAME_SPEED,
+};
 use crossterm::{
-    event, execute,
+    event::{self, DisableBracketedPaste, EnableBracketedPaste},
+    execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::Backend, prelude::*};
@@ -25,8 +31,17 @@ impl<B: Backend> Tui<B> {
     }
 
     pub fn init(&mut self) -> Result<()> {
+        // A panic would otherwise leave the terminal in raw mode, with its message lost on the
+        // alternate screen.
+        let panic_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let _ = disable_raw_mode();
+            let _ = execute!(stdout(), DisableBracketedPaste, LeaveAlternateScreen);
+            panic_hook(info);
+        }));
+
         enable_raw_mode()?;
-        execute!(stdout(), EnterAlternateScreen)?;
+        execute!(stdout(), EnterAlternateScreen, EnableBracketedPaste)?;
         self.terminal.hide_cursor()?;
         self.terminal.clear()?;
         Ok(())
@@ -34,33 +49,70 @@ impl<B: Backend> Tui<B> {
 
     pub fn run(&mut self, app: &mut App) -> Result<()> {
         loop {
+            let delta = self.now.elapsed();
             self.now = Instant::now();
+            let playing = app.game_started || app.attract;
+            if playing && !app.game.game_paused && !app.game.game_over {
+                app.game.advance_clock(delta);
+            }
+            let events = app.game.take_events();
+            // The match behind the main menu plays silently and without effects.
+            if !app.attract {
+                for event in events {
+                    app.audio.handle(event, &app.settings);
+                    app.announcer.handle(event, &app.game);
+                    if !app.settings.reduced_motion {
+                        app.effects.handle(event);
+                    }
+                }
+            }
+            if !app.settings.reduced_motion {
+                app.effects.update(delta);
+            }
             if app.should_quit {
                 return Ok(());
             }
 
-            if app.game_started && !app.game.game_paused {
-                self.frame_time =
-                    Duration::from_secs_f32(1.0 / (app.difficulty as u8 * 4 + 10) as f32);
+            if app.main_menu_shown && !app.attract && !app.help_popup_shown {
+                app.idle += delta;
+                if app.idle >= ATTRACT_DELAY {
+                    app.start_attract();
+                }
+            }
+
+            if playing && !app.game.game_paused {
+                // The assist slows the steps down without touching the difficulty.
+                self.frame_time = app
+                    .game
+                    .step_time()
+                    .mul_f32(MAX_GAME_SPEED as f32 / app.settings.game_speed as f32);
             } else {
                 self.frame_time = Duration::from_secs_f32(1.0 / 60.0);
             }
 
-            let mut update_b = false;
             self.handle_input(app)?;
             app.game.check_game_over();
+            if app.attract && app.game.game_over {
+                app.start_attract();
+            }
 
             if self.now.duration_since(self.last_update) >= self.frame_time {
                 self.last_update = Instant::now();
-                update_b = true;
+                if app.game_started || app.attract {
+                    app.game.step();
+                }
+            }
+            self.draw(app)?;
+            if app.announcer.is_open() {
+                let screen = announce::describe(app);
+                app.announcer.update(screen, &app.game);
             }
-            self.draw(app, update_b)?;
         }
     }
 
     pub fn exit(&mut self) -> Result<()> {
         disable_raw_mode()?;
-        execute!(stdout(), LeaveAlternateScreen)?;
+        execute!(stdout(), DisableBracketedPaste, LeaveAlternateScreen)?;
         Ok(())
     }
 
@@ -71,7 +123,7 @@ impl<B: Backend> Tui<B> {
         Ok(())
     }
 
-    pub fn draw(&mut self, app: &mut App, update_b: bool) -> Result<CompletedFrame> {
-        self.terminal.draw(|frame| drawer::ui(frame, app, update_b))
+    pub fn draw(&mut self, app: &mut App) -> Result<CompletedFrame<'_>> {
+        self.terminal.draw(|frame| drawer::ui(frame, app))
     }
 }